@@ -15,6 +15,8 @@ fn main() {
 
     // Loop through the glyphs in the text, adding to the SVG.
     let mut path = font.render("…hello‽É¿?üæ 2⸘" /*text*/, 2048.0 /*width*/).0;
+    // Size the image to fit the ink of the text
+    let ink = path.ink().unwrap();
     data = Data::new();
 
     for i in &mut path {
@@ -56,8 +58,8 @@ fn main() {
     let style =
         Style::new("path { fill: 0x000000; stroke: black; stroke-width: 3; }");
     let document = Document::new()
-        .set("width", 2048.0)
-        .set("height", 256.0)
+        .set("width", (ink.x + ink.width) * FONT_SIZE)
+        .set("height", (ink.y + ink.height) * FONT_SIZE)
         .add(style)
         .add(group);
     svg::save("image_example.svg", &document).unwrap();
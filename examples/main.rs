@@ -25,19 +25,28 @@ fn main() {
             2048.0 / 256.0,
             // fonterator::TextAlign::Left,
         );
+        // Only composite the part of the raster with ink on it
+        let ink = path.ink();
         // Clear plotter
         let mut pr = p.into_raster();
         pr.clear();
         p = Plotter::new(pr);
         p.set_transform(Transform::with_scale(256.0, 256.0));
+        let matte = p.fill(FillRule::NonZero, path, Matte8::new(255));
         // Composite
-        r.composite_matte(
-            (0, line * 256, 2048, 2048),
-            p.fill(FillRule::NonZero, path, Matte8::new(255)),
-            (),
-            Rgba8p::new(0, 0, 0, 255), /*color*/
-            SrcOver,
-        );
+        if let Some(ink) = ink {
+            let x = (ink.x * 256.0).floor() as i32;
+            let y = (ink.y * 256.0).floor() as i32;
+            let w = (ink.width * 256.0).ceil() as u32 + 1;
+            let h = (ink.height * 256.0).ceil() as u32 + 1;
+            r.composite_matte(
+                (x, line * 256 + y, w, h),
+                matte,
+                (x, y, w, h),
+                Rgba8p::new(0, 0, 0, 255), /*color*/
+                SrcOver,
+            );
+        }
         if let Some(l) = l {
             begin += l;
         }
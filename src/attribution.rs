@@ -0,0 +1,86 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! License attribution for the faces in a font, for third-party notices.
+
+use crate::name::{NameId, Names};
+
+/// Who made a face and how it's licensed, from
+/// [`Font::attributions()`](crate::Font::attributions).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attribution {
+    /// Full name of the face, like "DejaVu Sans Bold".
+    pub name: String,
+    /// Copyright holders of a bundled face, or else the copyright notice from
+    /// the face's `name` table.
+    pub copyright: Option<String>,
+    /// SPDX license identifier, like "OFL-1.1".  Faces that aren't bundled
+    /// only have one if their license is recognized from its description or
+    /// URL.
+    pub license: Option<&'static str>,
+    /// Full text of the license of a bundled face, or else the license
+    /// description from the face's `name` table.
+    pub text: Option<String>,
+    /// URL of the license.
+    pub url: Option<String>,
+}
+
+impl Attribution {
+    /// Get the attribution of a face, from the font file's data and the
+    /// face's `name` table.
+    pub(crate) fn new(data: &[u8], names: Names<'_>) -> Self {
+        let name = names
+            .get(NameId::FullName)
+            .or_else(|| names.get(NameId::Family))
+            .unwrap_or_default();
+
+        #[cfg(any(feature = "monospace-font", feature = "normal-font"))]
+        if let Some(license) = crate::bundled::license(data) {
+            return Self {
+                name,
+                copyright: Some(license.copyright.to_string()),
+                license: Some(license.spdx),
+                text: Some(license.text().to_string()),
+                url: Some(license.url.to_string()),
+            };
+        }
+        let _ = data;
+
+        let text = names.get(NameId::License);
+        let url = names.get(NameId::LicenseUrl);
+        Self {
+            name,
+            copyright: names.get(NameId::Copyright),
+            license: spdx(text.as_deref(), url.as_deref()),
+            text,
+            url,
+        }
+    }
+}
+
+// Recognize a few common font licenses from their description or URL.
+fn spdx(text: Option<&str>, url: Option<&str>) -> Option<&'static str> {
+    let both =
+        format!("{} {}", text.unwrap_or(""), url.unwrap_or("")).to_lowercase();
+    if both.contains("open font license")
+        || both.contains("scripts.sil.org/ofl")
+        || both.contains("openfontlicense.org")
+    {
+        Some("OFL-1.1")
+    } else if both.contains("apache license")
+        || both.contains("apache.org/licenses/license-2.0")
+    {
+        Some("Apache-2.0")
+    } else if both.contains("ubuntu font licence") {
+        Some("Ubuntu-font-1.0")
+    } else if both.contains("bitstream") {
+        Some("Bitstream-Vera")
+    } else {
+        None
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! Fonts bundled with the `monospace-font` and `normal-font` features, and
+//! their licenses.
+
+#[cfg(feature = "monospace-font")]
+pub(crate) static SANS_MONO: &[u8] = include_bytes!("font/dejavu/SansMono.ttf");
+#[cfg(feature = "monospace-font")]
+pub(crate) static SANS_MONO_BOLD: &[u8] =
+    include_bytes!("font/dejavu/SansMonoBold.ttf");
+#[cfg(feature = "monospace-font")]
+pub(crate) static SANS_MONO_OBLIQUE: &[u8] =
+    include_bytes!("font/dejavu/SansMonoOblique.ttf");
+#[cfg(feature = "monospace-font")]
+pub(crate) static SANS_MONO_BOLD_OBLIQUE: &[u8] =
+    include_bytes!("font/dejavu/SansMonoBoldOblique.ttf");
+
+#[cfg(feature = "normal-font")]
+pub(crate) static SANS: &[u8] = include_bytes!("font/dejavu/Sans.ttf");
+#[cfg(feature = "normal-font")]
+pub(crate) static SANS_BOLD: &[u8] = include_bytes!("font/dejavu/SansBold.ttf");
+#[cfg(feature = "normal-font")]
+pub(crate) static SANS_OBLIQUE: &[u8] =
+    include_bytes!("font/dejavu/SansOblique.ttf");
+#[cfg(feature = "normal-font")]
+pub(crate) static SANS_BOLD_OBLIQUE: &[u8] =
+    include_bytes!("font/dejavu/SansBoldOblique.ttf");
+
+pub(crate) static DEVANAGARI: &[u8] =
+    include_bytes!("font/noto/SansDevanagari.ttf");
+pub(crate) static HEBREW: &[u8] = include_bytes!("font/noto/SansHebrew.ttf");
+pub(crate) static FALLBACK: &[u8] =
+    include_bytes!("font/droid/SansFallback.ttf");
+
+/// The license of some of the bundled fonts.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct License {
+    // Copyright holders.
+    pub(crate) copyright: &'static str,
+    // SPDX license identifier.
+    pub(crate) spdx: &'static str,
+    // Where to find the license.
+    pub(crate) url: &'static str,
+    // Index of the license's section in `bin-licenses.txt`.
+    section: usize,
+}
+
+impl License {
+    /// Get the full text of the license, from `bin-licenses.txt`.
+    pub(crate) fn text(&self) -> &'static str {
+        let section = crate::licenses()
+            .split(SEPARATOR)
+            .nth(self.section)
+            .unwrap_or_default();
+        // Skip the heading, which names the fonts.
+        section
+            .split_once(HEADING)
+            .map_or(section, |(_, text)| text)
+            .trim()
+    }
+}
+
+// Line between the sections of `bin-licenses.txt`.
+const SEPARATOR: &str = "\n================================================================================\n";
+// Line below the heading of each section.
+const HEADING: &str = "\n##########\n";
+
+const DROID: License = License {
+    copyright: "The Android Open Source Project",
+    spdx: "Apache-2.0",
+    url: "https://www.apache.org/licenses/LICENSE-2.0",
+    section: 0,
+};
+
+const DEJAVU: License = License {
+    copyright: "Bitstream, Inc. and Tavmjong Bah (DejaVu changes are in the \
+                public domain)",
+    spdx: "Bitstream-Vera",
+    url: "https://dejavu-fonts.github.io/License.html",
+    section: 1,
+};
+
+const NOTO: License = License {
+    copyright: "Google Inc.",
+    spdx: "OFL-1.1",
+    url: "https://scripts.sil.org/OFL",
+    section: 2,
+};
+
+/// Get the license of a bundled font, from the font file's data, or `None`
+/// if it isn't one of the bundled fonts.
+pub(crate) fn license(data: &[u8]) -> Option<License> {
+    let mut fonts: Vec<(&[u8], License)> =
+        vec![(DEVANAGARI, NOTO), (HEBREW, NOTO), (FALLBACK, DROID)];
+    #[cfg(feature = "monospace-font")]
+    fonts.extend_from_slice(&[
+        (SANS_MONO, DEJAVU),
+        (SANS_MONO_BOLD, DEJAVU),
+        (SANS_MONO_OBLIQUE, DEJAVU),
+        (SANS_MONO_BOLD_OBLIQUE, DEJAVU),
+    ]);
+    #[cfg(feature = "normal-font")]
+    fonts.extend_from_slice(&[
+        (SANS, DEJAVU),
+        (SANS_BOLD, DEJAVU),
+        (SANS_OBLIQUE, DEJAVU),
+        (SANS_BOLD_OBLIQUE, DEJAVU),
+    ]);
+    fonts
+        .into_iter()
+        .find(|(font, _)| std::ptr::eq(*font, data))
+        .map(|(_, license)| license)
+}
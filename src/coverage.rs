@@ -0,0 +1,69 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! Unicode coverage of faces, from their `cmap` tables.
+
+use rustybuzz::ttf_parser::Face;
+use std::ops::RangeInclusive;
+
+/// Get the code points a face has glyphs for, from its Unicode `cmap`
+/// subtables, as sorted ranges.
+pub(crate) fn ranges(face: &Face<'_>) -> Vec<RangeInclusive<u32>> {
+    let mut code_points = Vec::new();
+    if let Some(cmap) = face.tables().cmap {
+        for subtable in cmap.subtables {
+            if !subtable.is_unicode() {
+                continue;
+            }
+            subtable.codepoints(|c| {
+                if subtable.glyph_index(c).is_some_and(|glyph| glyph.0 != 0) {
+                    code_points.push(c);
+                }
+            });
+        }
+    }
+    code_points.sort_unstable();
+    code_points.dedup();
+
+    let mut ranges: Vec<RangeInclusive<u32>> = Vec::new();
+    for c in code_points {
+        match ranges.last_mut() {
+            Some(range) if *range.end() + 1 == c => {
+                *range = *range.start()..=c;
+            }
+            _ => ranges.push(c..=c),
+        }
+    }
+    ranges
+}
+
+/// Whether sorted ranges of code points contain a character.
+pub(crate) fn contains(ranges: &[RangeInclusive<u32>], c: char) -> bool {
+    let c = u32::from(c);
+    let i = ranges.partition_point(|range| *range.end() < c);
+    ranges.get(i).is_some_and(|range| range.contains(&c))
+}
+
+/// Whether a character is drawn without a glyph of its own: control
+/// characters, and invisible formatting characters like joiners and
+/// variation selectors.
+pub(crate) fn invisible(c: char) -> bool {
+    c.is_control()
+        || matches!(
+            c,
+            '\u{00AD}'
+                | '\u{034F}'
+                | '\u{180B}'..='\u{180F}'
+                | '\u{200B}'..='\u{200F}'
+                | '\u{202A}'..='\u{202E}'
+                | '\u{2060}'..='\u{206F}'
+                | '\u{FE00}'..='\u{FE0F}'
+                | '\u{FEFF}'
+                | '\u{E0000}'..='\u{E0FFF}'
+        )
+}
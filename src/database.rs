@@ -0,0 +1,320 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! An index of the fonts installed in local directories.
+
+use crate::coverage;
+use crate::font::Font;
+use crate::query::{self, Query};
+use crate::variant::Variant;
+use rustybuzz::ttf_parser::{fonts_in_collection, Face};
+use std::cell::OnceCell;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+/// A face indexed by [`FontDatabase::scan()`].
+#[derive(Clone, Debug)]
+pub struct FaceInfo {
+    /// Path of the font file.
+    pub path: PathBuf,
+    /// Index of the face within the file (more than 0 only in font
+    /// collections).
+    pub index: u32,
+    /// Family names, legacy and typographic, in every language listed.
+    pub families: Vec<String>,
+    /// Weight, slant and stretch.
+    pub variant: Variant,
+    // Index of the file within `FontDatabase::files`.
+    file: usize,
+    // Code points with glyphs, as sorted ranges.
+    coverage: Vec<RangeInclusive<u32>>,
+}
+
+impl FaceInfo {
+    /// Whether the face has a glyph for a character.
+    pub fn covers(&self, c: char) -> bool {
+        coverage::contains(&self.coverage, c)
+    }
+
+    /// Get the code points the face has glyphs for, as sorted ranges.
+    pub fn coverage(&self) -> &[RangeInclusive<u32>] {
+        &self.coverage
+    }
+
+    // Whether both faces share a family name.
+    fn same_family(&self, other: &Self) -> bool {
+        self.families.iter().any(|name| {
+            other
+                .families
+                .iter()
+                .any(|other| name.eq_ignore_ascii_case(other))
+        })
+    }
+}
+
+/// An index of TTF, OTF, TTC, WOFF and WOFF2 files in local directories, to
+/// build [`Font`]s from by family name and style instead of by hand.
+///
+/// Font files are only read while indexing them, and again when they're
+/// first needed for a `Font`.
+///
+/// ```rust,no_run
+/// use fonterator::{FontDatabase, Query};
+///
+/// let mut database = FontDatabase::new();
+/// database.scan("/usr/share/fonts").unwrap();
+/// let font = database
+///     .font(&[Query::new().family("DejaVu Sans")], "Hello, 世界")
+///     .unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct FontDatabase {
+    faces: Vec<FaceInfo>,
+    // Paths of the indexed files, with their data once read (`None` if
+    // reading failed).
+    files: Vec<(PathBuf, OnceCell<Option<Vec<u8>>>)>,
+}
+
+impl FontDatabase {
+    /// Create an empty database.  Add fonts with `scan()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index every face in the font files (`.ttf`, `.otf`, `.ttc`, `.otc`,
+    /// and with feature = "woff", `.woff` and `.woff2`) in a directory and its
+    /// subdirectories.  Symbolic links are followed, but each directory is
+    /// only scanned once, even if links lead back to it.  Subdirectories and
+    /// files that can't be read or parsed are skipped.  Returns an error only
+    /// if `dir` itself can't be read.
+    pub fn scan<P: AsRef<Path>>(&mut self, dir: P) -> io::Result<()> {
+        self.scan_dir(dir.as_ref(), &mut HashSet::new())
+    }
+
+    // Index the font files in a directory and its subdirectories, skipping
+    // directories whose canonical path is in `visited`.
+    fn scan_dir(
+        &mut self,
+        dir: &Path,
+        visited: &mut HashSet<PathBuf>,
+    ) -> io::Result<()> {
+        let entries = fs::read_dir(dir)?;
+        if !visited.insert(fs::canonicalize(dir)?) {
+            return Ok(());
+        }
+        let mut entries: Vec<(PathBuf, bool)> = entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let path = entry.path();
+                // `DirEntry::file_type()` doesn't follow symbolic links.
+                let file_type = entry.file_type().ok()?;
+                let is_dir = if file_type.is_symlink() {
+                    fs::metadata(&path).is_ok_and(|meta| meta.is_dir())
+                } else {
+                    file_type.is_dir()
+                };
+                Some((path, is_dir))
+            })
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (path, is_dir) in entries {
+            if is_dir {
+                let _ = self.scan_dir(&path, visited);
+            } else if is_font_file(&path) {
+                self.index(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Get every indexed face, in the order they were found.
+    pub fn faces(&self) -> &[FaceInfo] {
+        &self.faces
+    }
+
+    /// Find the indexed face that best fits a [`Query`], with the same rules
+    /// as [`Font::find()`].
+    pub fn find(&self, query: &Query) -> Option<&FaceInfo> {
+        Some(&self.faces[self.find_index(query)?])
+    }
+
+    /// Build a [`Font`] with a fallback font for each query, in order, along
+    /// with the other faces of its family as style variants.  Then, for any
+    /// characters of `text` that none of those cover, fonts (and their
+    /// families) that do are added as further fallbacks.  Returns `None` if
+    /// no faces were found.
+    pub fn font(&self, queries: &[Query], text: &str) -> Option<Font<'_>> {
+        let mut font = Font::new();
+        let mut used: Vec<usize> = Vec::new();
+
+        for query in queries {
+            if let Some(face) = self.find_index(query) {
+                self.add_family(&mut font, &mut used, face);
+            }
+        }
+        for c in text.chars().filter(|c| !c.is_control()) {
+            if used.iter().any(|face| self.faces[*face].covers(c)) {
+                continue;
+            }
+            // Prefer a regular face for fallbacks.
+            let covering: Vec<usize> = (0..self.faces.len())
+                .filter(|face| self.faces[*face].covers(c))
+                .collect();
+            let closest = Variant::REGULAR
+                .closest(covering.iter().map(|face| self.faces[*face].variant));
+            if let Some(i) = closest {
+                self.add_family(&mut font, &mut used, covering[i]);
+            }
+        }
+
+        if used.is_empty() {
+            return None;
+        }
+        Some(font)
+    }
+
+    // Find the index of the face that best fits a query.
+    fn find_index(&self, query: &Query) -> Option<usize> {
+        let faces: Vec<usize> = (0..self.faces.len()).collect();
+        query::find(
+            query,
+            &faces,
+            |face, family| {
+                self.faces[face]
+                    .families
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(family))
+            },
+            |face| self.faces[face].variant,
+        )
+    }
+
+    // Add a face to a `Font` as a new fallback font, followed by the rest of
+    // its family as style variants, unless it's already been added.
+    fn add_family<'a>(
+        &'a self,
+        font: &mut Font<'a>,
+        used: &mut Vec<usize>,
+        face: usize,
+    ) {
+        if used.contains(&face) {
+            return;
+        }
+        let first = &self.faces[face];
+        match self.data(first.file) {
+            Some(data) if font.add(data, first.index).is_some() => {}
+            _ => return,
+        }
+        used.push(face);
+
+        for (i, other) in self.faces.iter().enumerate() {
+            if used.contains(&i) || !other.same_family(first) {
+                continue;
+            }
+            if let Some(data) = self.data(other.file) {
+                if font.add_variant(data, other.index).is_some() {
+                    used.push(i);
+                }
+            }
+        }
+    }
+
+    // Get the data of a file, reading it the first time.
+    fn data(&self, file: usize) -> Option<&[u8]> {
+        let (path, data) = &self.files[file];
+        data.get_or_init(|| read(path)).as_deref()
+    }
+
+    // Index the faces in a font file.
+    fn index(&mut self, path: PathBuf) {
+        let data = match read(&path) {
+            Some(data) => data,
+            None => return,
+        };
+        let file = self.files.len();
+        let count = fonts_in_collection(&data).unwrap_or(1);
+        for index in 0..count {
+            let face = match Face::parse(&data, index) {
+                Ok(face) => face,
+                Err(_) => continue,
+            };
+            self.faces.push(FaceInfo {
+                path: path.clone(),
+                index,
+                families: query::families(&face),
+                variant: Variant::of(&face),
+                file,
+                coverage: coverage::ranges(&face),
+            });
+        }
+        if self.faces.last().is_some_and(|face| face.file == file) {
+            self.files.push((path, OnceCell::new()));
+        }
+    }
+}
+
+// Read a font file, decoding WOFF and WOFF2 files.
+fn read(path: &Path) -> Option<Vec<u8>> {
+    let data = fs::read(path).ok()?;
+    #[cfg(feature = "woff")]
+    if crate::woff::is_woff(&data) {
+        return crate::woff::decode_woff(&data);
+    }
+    Some(data)
+}
+
+// Whether a path has the extension of a font file.
+fn is_font_file(path: &Path) -> bool {
+    let extension = path.extension().and_then(OsStr::to_str);
+    let extensions: &[&str] = if cfg!(feature = "woff") {
+        &["ttf", "otf", "ttc", "otc", "woff", "woff2"]
+    } else {
+        &["ttf", "otf", "ttc", "otc"]
+    };
+    extension.is_some_and(|extension| {
+        extensions
+            .iter()
+            .any(|font| extension.eq_ignore_ascii_case(font))
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn scan_symlinks() {
+        let dir = std::env::temp_dir()
+            .join(format!("fonterator-scan-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("fonts/nested")).unwrap();
+        fs::create_dir_all(dir.join("scan")).unwrap();
+        let ttf = include_bytes!("woff/tiny.ttf");
+        fs::write(dir.join("fonts/nested/tiny.ttf"), ttf).unwrap();
+        // A linked directory, and a link back to its parent.
+        symlink(dir.join("fonts"), dir.join("scan/fonts")).unwrap();
+        symlink(dir.join("fonts"), dir.join("fonts/nested/loop")).unwrap();
+
+        let mut database = FontDatabase::new();
+        database.scan(dir.join("scan")).unwrap();
+        let paths: Vec<&Path> = database
+            .faces()
+            .iter()
+            .map(|face| face.path.as_path())
+            .collect();
+        assert_eq!(paths, [dir.join("scan/fonts/nested/tiny.ttf")]);
+        assert!(database.scan(dir.join("missing")).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
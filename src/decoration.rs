@@ -0,0 +1,418 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! Text decorations: underline, strikethrough and overline.
+
+use crate::layout::Layout;
+use crate::outline::Outline;
+use crate::style::DecorationStyle;
+use rustybuzz::ttf_parser::Face;
+
+/// Position and thickness of a decoration line (scaled).
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Stroke {
+    // Top edge, measured upwards from the baseline.
+    pub(crate) top: f32,
+    // Thickness of the line.
+    pub(crate) thickness: f32,
+}
+
+impl Stroke {
+    /// Get the underline of a face, from the `post` table.
+    pub(crate) fn underline(face: &Face<'_>, scale: f32) -> Self {
+        let thickness = thickness(face, scale);
+        let top = match face.underline_metrics() {
+            Some(metrics) if metrics.thickness > 0 => {
+                f32::from(metrics.position) * scale
+            }
+            _ => f32::from(face.descender()) * scale / 2.0,
+        };
+        Self { top, thickness }
+    }
+
+    /// Get the strikethrough of a face, from the `OS/2` table.
+    pub(crate) fn strikethrough(face: &Face<'_>, scale: f32) -> Self {
+        match face.strikeout_metrics() {
+            Some(metrics) if metrics.thickness > 0 => Self {
+                top: f32::from(metrics.position) * scale,
+                thickness: f32::from(metrics.thickness) * scale,
+            },
+            _ => {
+                // Centered on half the x-height.
+                let thickness = thickness(face, scale);
+                let x_height = match face.x_height() {
+                    Some(x_height) => f32::from(x_height) * scale,
+                    None => f32::from(face.ascender()) * scale / 2.0,
+                };
+                Self {
+                    top: (x_height + thickness) / 2.0,
+                    thickness,
+                }
+            }
+        }
+    }
+
+    /// Get the overline of a face: an underline-thick line just inside the
+    /// top of the line.
+    pub(crate) fn overline(face: &Face<'_>, scale: f32) -> Self {
+        Self {
+            top: f32::from(face.ascender()) * scale,
+            thickness: thickness(face, scale),
+        }
+    }
+
+    /// Stream the decoration from `left` to `right` into an outline, as
+    /// filled shapes with the same winding as TrueType glyph outlines.
+    /// Patterns are lined up with X = 0, so separate parts join up.
+    pub(crate) fn fill<O: Outline>(
+        &self,
+        layout: Layout,
+        baseline: f32,
+        (left, right): (f32, f32),
+        style: DecorationStyle,
+        out: &mut O,
+    ) {
+        let t = self.thickness;
+        let top = baseline + self.top;
+        let band =
+            |a: f32, b: f32| [(a, top), (b, top), (b, top - t), (a, top - t)];
+        match style {
+            DecorationStyle::Solid => polygon(layout, &band(left, right), out),
+            DecorationStyle::Dashed => {
+                // Dashes three times as long as the gaps between them.
+                let period = 4.0 * t;
+                let mut x = (left / period).floor() * period;
+                while x < right {
+                    let (a, b) = (x.max(left), (x + 3.0 * t).min(right));
+                    if b > a {
+                        polygon(layout, &band(a, b), out);
+                    }
+                    x += period;
+                }
+            }
+            DecorationStyle::Wavy => {
+                // A sine wave with an amplitude of the thickness.
+                let wavelength = 6.0 * t;
+                let step = wavelength / 16.0;
+                let middle = top - t / 2.0;
+                let wave = |x: f32| {
+                    middle + t * (x * std::f32::consts::TAU / wavelength).sin()
+                };
+                let mut xs = vec![left];
+                let mut x = (left / step).floor() * step + step;
+                while x < right {
+                    xs.push(x);
+                    x += step;
+                }
+                xs.push(right);
+                let upper = xs.iter().map(|x| (*x, wave(*x) + t / 2.0));
+                let lower = xs.iter().rev().map(|x| (*x, wave(*x) - t / 2.0));
+                let points: Vec<(f32, f32)> = upper.chain(lower).collect();
+                polygon(layout, &points, out);
+            }
+            DecorationStyle::Dotted => {
+                // Round dots, one thickness apart.
+                let period = 2.0 * t;
+                let r = t / 2.0;
+                let mut cx = (left / period).floor() * period + r;
+                while cx < right {
+                    if cx - r >= left - f32::EPSILON
+                        && cx + r <= right + f32::EPSILON
+                    {
+                        dot(layout, (cx, top - r), r, out);
+                    }
+                    cx += period;
+                }
+            }
+        }
+    }
+}
+
+// Stream a closed polygon, given upwards from the origin.
+fn polygon<O: Outline>(layout: Layout, points: &[(f32, f32)], out: &mut O) {
+    for (i, (x, y)) in points.iter().enumerate() {
+        if i == 0 {
+            out.move_to(*x, layout.y(*y));
+        } else {
+            out.line_to(*x, layout.y(*y));
+        }
+    }
+    out.close();
+}
+
+// Stream a circle (clockwise, like the polygons), given upwards from the
+// origin.
+fn dot<O: Outline>(layout: Layout, (cx, cy): (f32, f32), r: f32, out: &mut O) {
+    // Distance of the control points for a quarter circle of cubic béziers.
+    let k = r * 0.552_284_8;
+    let y = |y: f32| layout.y(cy + y);
+    out.move_to(cx, y(r));
+    out.cubic_to(cx + k, y(r), cx + r, y(k), cx + r, y(0.0));
+    out.cubic_to(cx + r, y(-k), cx + k, y(-r), cx, y(-r));
+    out.cubic_to(cx - k, y(-r), cx - r, y(-k), cx - r, y(0.0));
+    out.cubic_to(cx - r, y(k), cx - k, y(r), cx, y(r));
+    out.close();
+}
+
+// Get the thickness of the underline of a face, from the `post` table, or
+// about a twentieth of an em if it's missing.
+fn thickness(face: &Face<'_>, scale: f32) -> f32 {
+    match face.underline_metrics() {
+        Some(metrics) if metrics.thickness > 0 => {
+            f32::from(metrics.thickness) * scale
+        }
+        _ => f32::from(face.units_per_em()) * scale / 20.0,
+    }
+}
+
+/// An outline sink that finds where glyph ink crosses a horizontal band, to
+/// skip an underline around descenders.
+#[derive(Debug)]
+pub(crate) struct InkSkip {
+    // Smallest and largest Y coordinate of the band.
+    band: (f32, f32),
+    // Start of the current contour.
+    start: (f32, f32),
+    // Current point.
+    pen: (f32, f32),
+    // Outline flattened into line segments.
+    segments: Vec<((f32, f32), (f32, f32))>,
+}
+
+impl InkSkip {
+    /// Create a sink for the band between two Y coordinates.
+    pub(crate) fn new(a: f32, b: f32) -> Self {
+        Self {
+            band: (a.min(b), a.max(b)),
+            start: (0.0, 0.0),
+            pen: (0.0, 0.0),
+            segments: Vec::new(),
+        }
+    }
+
+    /// Split the span from `left` to `right` into the parts that don't come
+    /// within `pad` of any ink in the band.
+    pub(crate) fn skip(
+        &self,
+        (left, right): (f32, f32),
+        pad: f32,
+    ) -> Vec<(f32, f32)> {
+        let mut ink = self.ink();
+        ink.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut parts = Vec::new();
+        let mut x = left;
+        for (a, b) in ink {
+            if a - pad > x {
+                parts.push((x, (a - pad).min(right)));
+            }
+            x = x.max(b + pad);
+            if x >= right {
+                break;
+            }
+        }
+        if x < right {
+            parts.push((x, right));
+        }
+        parts.retain(|(a, b)| b > a);
+        parts
+    }
+
+    // Get the horizontal ranges of ink within the band: the parts of the
+    // outline inside it, and the filled spans along its edges and middle.
+    fn ink(&self) -> Vec<(f32, f32)> {
+        let (lo, hi) = self.band;
+        let mut ink = Vec::new();
+        for &((ax, ay), (bx, by)) in &self.segments {
+            let (t0, t1) = if (by - ay).abs() <= f32::EPSILON {
+                if ay < lo || ay > hi {
+                    continue;
+                }
+                (0.0, 1.0)
+            } else {
+                let t = |y: f32| ((y - ay) / (by - ay)).clamp(0.0, 1.0);
+                let (t0, t1) = (t(lo), t(hi));
+                if ay.max(by) < lo || ay.min(by) > hi {
+                    continue;
+                }
+                (t0.min(t1), t0.max(t1))
+            };
+            let x0 = ax + (bx - ax) * t0;
+            let x1 = ax + (bx - ax) * t1;
+            ink.push((x0.min(x1), x0.max(x1)));
+        }
+        for y in [lo, (lo + hi) / 2.0, hi] {
+            ink.extend(self.filled(y));
+        }
+        ink
+    }
+
+    // Get the filled spans along a horizontal line (non-zero winding).
+    fn filled(&self, y: f32) -> Vec<(f32, f32)> {
+        let mut crossings: Vec<(f32, i32)> = Vec::new();
+        for &((ax, ay), (bx, by)) in &self.segments {
+            if (ay <= y) != (by <= y) {
+                let x = ax + (bx - ax) * (y - ay) / (by - ay);
+                crossings.push((x, if by > ay { 1 } else { -1 }));
+            }
+        }
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut spans = Vec::new();
+        let mut winding = 0;
+        let mut start = 0.0;
+        for (x, dir) in crossings {
+            if winding == 0 {
+                start = x;
+            }
+            winding += dir;
+            if winding == 0 {
+                spans.push((start, x));
+            }
+        }
+        spans
+    }
+
+    fn segment(&mut self, to: (f32, f32)) {
+        self.segments.push((self.pen, to));
+        self.pen = to;
+    }
+}
+
+impl Outline for InkSkip {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.start = (x, y);
+        self.pen = (x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.segment((x, y));
+    }
+
+    fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32) {
+        let (px, py) = self.pen;
+        for i in 1..=8 {
+            let t = i as f32 / 8.0;
+            let s = 1.0 - t;
+            self.segment((
+                s * s * px + 2.0 * s * t * cx + t * t * x,
+                s * s * py + 2.0 * s * t * cy + t * t * y,
+            ));
+        }
+    }
+
+    fn cubic_to(&mut self, ax: f32, ay: f32, bx: f32, by: f32, x: f32, y: f32) {
+        let (px, py) = self.pen;
+        for i in 1..=8 {
+            let t = i as f32 / 8.0;
+            let s = 1.0 - t;
+            self.segment((
+                s * s * s * px
+                    + 3.0 * s * s * t * ax
+                    + 3.0 * s * t * t * bx
+                    + t * t * t * x,
+                s * s * s * py
+                    + 3.0 * s * s * t * ay
+                    + 3.0 * s * t * t * by
+                    + t * t * t * y,
+            ));
+        }
+    }
+
+    fn close(&mut self) {
+        if self.pen != self.start {
+            self.segment(self.start);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Horizontal extent of each contour streamed into it.
+    #[derive(Default)]
+    struct Extents(Vec<(f32, f32)>);
+
+    impl Extents {
+        fn point(&mut self, x: f32) {
+            let last = self.0.last_mut().unwrap();
+            *last = (last.0.min(x), last.1.max(x));
+        }
+    }
+
+    impl Outline for Extents {
+        fn move_to(&mut self, x: f32, _y: f32) {
+            self.0.push((x, x));
+        }
+
+        fn line_to(&mut self, x: f32, _y: f32) {
+            self.point(x);
+        }
+
+        fn quad_to(&mut self, _cx: f32, _cy: f32, x: f32, _y: f32) {
+            self.point(x);
+        }
+
+        fn cubic_to(&mut self, _: f32, _: f32, _: f32, _: f32, x: f32, _: f32) {
+            self.point(x);
+        }
+
+        fn close(&mut self) {}
+    }
+
+    #[test]
+    fn patterns() {
+        let stroke = Stroke {
+            top: -1.0,
+            thickness: 1.0,
+        };
+        for (style, left, extents) in [
+            (DecorationStyle::Solid, 0.0, vec![(0.0, 10.0)]),
+            (DecorationStyle::Wavy, 0.0, vec![(0.0, 10.0)]),
+            // Dashes and dots line up with X = 0, wherever they start.
+            (
+                DecorationStyle::Dashed,
+                1.0,
+                vec![(1.0, 3.0), (4.0, 7.0), (8.0, 10.0)],
+            ),
+            (
+                DecorationStyle::Dotted,
+                1.0,
+                vec![(2.0, 3.0), (4.0, 5.0), (6.0, 7.0), (8.0, 9.0)],
+            ),
+        ] {
+            let mut out = Extents::default();
+            let layout = Layout::default();
+            stroke.fill(layout, 0.0, (left, 10.0), style, &mut out);
+            assert_eq!(out.0, extents, "{:?}", style);
+        }
+    }
+
+    #[test]
+    fn ink_skip() {
+        let mut ink = InkSkip::new(-1.0, 1.0);
+        let mut square = |(left, right): (f32, f32),
+                          (low, high): (f32, f32)| {
+            ink.move_to(left, low);
+            ink.line_to(left, high);
+            ink.line_to(right, high);
+            ink.line_to(right, low);
+            ink.close();
+        };
+        // A descender through the band, a glyph above it, and a dot inside.
+        square((2.0, 3.0), (-5.0, 5.0));
+        square((4.0, 5.0), (2.0, 8.0));
+        square((7.0, 7.5), (-0.5, 0.5));
+        assert_eq!(
+            ink.skip((0.0, 10.0), 0.5),
+            [(0.0, 1.5), (3.5, 6.5), (8.0, 10.0)]
+        );
+        // Spans covered by ink, and clear of it.
+        assert_eq!(ink.skip((2.5, 3.2), 0.5), []);
+        assert_eq!(ink.skip((3.0, 6.0), 0.0), [(3.0, 6.0)]);
+    }
+}
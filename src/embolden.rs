@@ -0,0 +1,148 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! Synthetic bold, by dilating glyph outlines.
+
+use rustybuzz::ttf_parser::OutlineBuilder;
+
+// A path segment, ending at the last of its points.
+#[derive(Copy, Clone, Debug)]
+enum Segment {
+    Move,
+    Line,
+    Quad,
+    Curve,
+    Close,
+}
+
+/// Collects a glyph outline (in font units), so that it can be dilated
+/// before being passed on.
+#[derive(Debug, Default)]
+pub(crate) struct Embolden {
+    // Segments, with the number of the first point of each one.
+    segments: Vec<(Segment, usize)>,
+    // Every point, on and off the curve.
+    points: Vec<(f32, f32)>,
+    // Ranges of `points` that make up each contour (a closing point on top
+    // of the first is skipped over when looking for neighbors).
+    contours: Vec<(usize, usize)>,
+}
+
+impl Embolden {
+    /// Move every point of the outline `offset` font units outwards, and pass
+    /// the result on to `out`.
+    pub(crate) fn finish<B: OutlineBuilder>(self, offset: f32, out: &mut B) {
+        // Outlines are clockwise in TrueType fonts, and counter-clockwise in
+        // CFF fonts, which changes which side is outwards.
+        let area: f32 = self
+            .contours
+            .iter()
+            .map(|(start, end)| area(&self.points[*start..*end]))
+            .sum();
+        let outwards = if area < 0.0 { 1.0 } else { -1.0 };
+
+        let mut moved = self.points.clone();
+        for (start, end) in self.contours.iter().cloned() {
+            let contour = &self.points[start..end];
+            for i in 0..contour.len() {
+                let (x, y) = contour[i];
+                let prev = neighbor(contour, i, contour.len() - 1);
+                let (prev, next) = match (prev, neighbor(contour, i, 1)) {
+                    (Some(prev), Some(next)) => (prev, next),
+                    _ => continue,
+                };
+                // Outward normals of the edges before and after the point.
+                let normal = |(ax, ay): (f32, f32), (bx, by): (f32, f32)| {
+                    let (dx, dy) = (bx - ax, by - ay);
+                    let len = (dx * dx + dy * dy).sqrt();
+                    (-dy / len * outwards, dx / len * outwards)
+                };
+                let a = normal(prev, (x, y));
+                let b = normal((x, y), next);
+                // Keep the new edges parallel to the old ones, limiting how
+                // far sharp corners can reach.
+                let d = (1.0 + a.0 * b.0 + a.1 * b.1).max(0.25);
+                moved[start + i] = (
+                    x + (a.0 + b.0) * offset / d,
+                    y + (a.1 + b.1) * offset / d,
+                );
+            }
+        }
+
+        for (segment, i) in self.segments {
+            let p = &moved[i..];
+            match segment {
+                Segment::Move => out.move_to(p[0].0, p[0].1),
+                Segment::Line => out.line_to(p[0].0, p[0].1),
+                Segment::Quad => out.quad_to(p[0].0, p[0].1, p[1].0, p[1].1),
+                Segment::Curve => {
+                    out.curve_to(p[0].0, p[0].1, p[1].0, p[1].1, p[2].0, p[2].1)
+                }
+                Segment::Close => out.close(),
+            }
+        }
+    }
+
+    fn push(&mut self, segment: Segment, points: &[(f32, f32)]) {
+        self.segments.push((segment, self.points.len()));
+        self.points.extend_from_slice(points);
+    }
+}
+
+impl OutlineBuilder for Embolden {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.push(Segment::Move, &[(x, y)]);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.push(Segment::Line, &[(x, y)]);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.push(Segment::Quad, &[(x1, y1), (x, y)]);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.push(Segment::Curve, &[(x1, y1), (x2, y2), (x, y)]);
+    }
+
+    fn close(&mut self) {
+        let start = self.contours.last().map_or(0, |contour| contour.1);
+        self.contours.push((start, self.points.len()));
+        self.push(Segment::Close, &[]);
+    }
+}
+
+// Get the signed area of a polygon (positive if counter-clockwise, with the
+// Y axis upwards).
+fn area(points: &[(f32, f32)]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..points.len() {
+        let (ax, ay) = points[i];
+        let (bx, by) = points[(i + 1) % points.len()];
+        area += ax * by - bx * ay;
+    }
+    area / 2.0
+}
+
+// Find the nearest point in one direction (`step` of 1 for next, or length
+// minus 1 for previous) that isn't on top of point `i`.
+fn neighbor(
+    contour: &[(f32, f32)],
+    i: usize,
+    step: usize,
+) -> Option<(f32, f32)> {
+    let mut j = (i + step) % contour.len();
+    while j != i {
+        if contour[j] != contour[i] {
+            return Some(contour[j]);
+        }
+        j = (j + step) % contour.len();
+    }
+    None
+}
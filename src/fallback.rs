@@ -0,0 +1,133 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! Rules for picking fallback faces by character range, script and language.
+
+use std::ops::RangeInclusive;
+use unicode_script::UnicodeScript;
+
+/// A rule that picks faces for some characters ahead of the order they were
+/// pushed in, set with
+/// [`Font::set_fallback_rules()`](crate::Font::set_fallback_rules).  A rule
+/// with no conditions applies to every character.
+///
+/// ```rust
+/// use fonterator::FallbackRule;
+///
+/// let rules = vec![
+///     // Han characters in Japanese text from face 3, and in Simplified
+///     // Chinese text from face 4.
+///     FallbackRule::new(&[3]).script("Hani").language("ja"),
+///     FallbackRule::new(&[4]).script("Hani").language("zh-Hans"),
+///     // Emoji from face 5 first.
+///     FallbackRule::new(&[5]).range('\u{1F300}'..='\u{1FAFF}'),
+/// ];
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FallbackRule {
+    pub(crate) faces: Vec<usize>,
+    pub(crate) range: Option<RangeInclusive<char>>,
+    pub(crate) script: Option<String>,
+    pub(crate) language: Option<String>,
+}
+
+impl FallbackRule {
+    /// Create a rule that tries the faces at `faces` (indices in the order
+    /// they were pushed), in order, before falling back as usual.
+    pub fn new(faces: &[usize]) -> Self {
+        Self {
+            faces: faces.to_vec(),
+            range: None,
+            script: None,
+            language: None,
+        }
+    }
+
+    /// Only apply to characters within `range`.
+    pub fn range(mut self, range: RangeInclusive<char>) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// Only apply to characters of a script, by its ISO 15924 code, like
+    /// "Hani" for Han or "Arab" for Arabic.
+    pub fn script(mut self, script: &str) -> Self {
+        self.script = Some(script.to_string());
+        self
+    }
+
+    /// Only apply to text in a language, by its BCP 47 tag (see
+    /// [`Style::language()`](crate::Style::language)).  The rule also
+    /// applies to more specific tags, so "zh" covers "zh-Hant" too.
+    pub fn language(mut self, language: &str) -> Self {
+        self.language = Some(language.to_string());
+        self
+    }
+
+    // Whether the rule applies to a character in text of a language.
+    pub(crate) fn applies(&self, c: char, language: Option<&str>) -> bool {
+        let in_range = self.range.as_ref().is_none_or(|r| r.contains(&c));
+        let in_script = self.script.as_ref().is_none_or(|script| {
+            c.script().short_name().eq_ignore_ascii_case(script)
+        });
+        let in_language = self.language.as_ref().is_none_or(|rule| {
+            language.is_some_and(|language| {
+                language.eq_ignore_ascii_case(rule)
+                    || language.get(..rule.len()).is_some_and(|prefix| {
+                        prefix.eq_ignore_ascii_case(rule)
+                            && language[rule.len()..].starts_with('-')
+                    })
+            })
+        });
+        in_range && in_script && in_language
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies() {
+        let rule = || FallbackRule::new(&[1]);
+        let han = || rule().script("Hani");
+        let zh = || rule().language("zh");
+        for (rule, c, language, applies) in [
+            (rule(), 'a', None, true),
+            // Ranges are inclusive.
+            (rule().range('a'..='c'), 'c', None, true),
+            (rule().range('a'..='c'), 'd', None, false),
+            // Scripts by ISO 15924 code, in any case.
+            (han(), '漢', None, true),
+            (rule().script("hani"), '漢', None, true),
+            (han(), 'か', None, false),
+            (rule().script("Hira"), 'か', None, true),
+            // Languages, and more specific tags, in any case.
+            (zh(), '漢', Some("zh"), true),
+            (zh(), '漢', Some("zh-Hant"), true),
+            (zh(), '漢', Some("ZH-hant"), true),
+            (zh(), '漢', Some("zhx"), false),
+            (zh(), '漢', Some("z"), false),
+            (zh(), '漢', None, false),
+            (rule().language("zh-Hant"), '漢', Some("zh"), false),
+            // Every condition must hold.
+            (han().language("ja"), '漢', Some("ja-JP"), true),
+            (han().language("ja"), '漢', Some("zh"), false),
+            (han().language("ja"), 'a', Some("ja"), false),
+        ] {
+            assert_eq!(
+                rule.applies(c, language),
+                applies,
+                "{:?} {} {:?}",
+                rule,
+                c,
+                language
+            );
+        }
+    }
+}
@@ -7,123 +7,175 @@
 // At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
 // LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
 
-use crate::direction::{direction, Direction};
+use crate::attribution::Attribution;
+#[cfg(any(feature = "monospace-font", feature = "normal-font"))]
+use crate::bundled;
+use crate::coverage;
+use crate::decoration::{InkSkip, Stroke};
+use crate::embolden::Embolden;
+use crate::fallback::FallbackRule;
+use crate::gdef;
+use crate::layout::{Layout, LineMetrics, Placed, Rect};
+use crate::name::Names;
+use crate::outline::Outline;
+use crate::paragraph::{Line, Paragraph};
+use crate::query::{self, Match, Query};
+use crate::render::Outliner;
+use crate::shape;
+use crate::style::{DecorationStyle, Style};
+use crate::variant::Variant;
+#[cfg(feature = "footile")]
 use footile::PathOp;
-use pointy::Pt;
 use rustybuzz::{
-    Face as FaceShaper, GlyphBuffer, GlyphInfo, GlyphPosition, UnicodeBuffer,
-    ttf_parser::{kern::Subtable, Face, GlyphId, OutlineBuilder},
+    ttf_parser::{Face, GlyphId},
+    Face as FaceShaper, GlyphBuffer,
 };
+use std::ops::{Range, RangeInclusive};
+use unicode_bidi::{BidiInfo, Level};
+use unicode_script::{Script, UnicodeScript};
+use unicode_segmentation::UnicodeSegmentation;
 
-struct LangFont<'a>(Face<'a>, FaceShaper<'a>);
-
-struct Outliner<'a> {
-    // Path to write out to.
-    path: &'a mut Vec<PathOp>,
-    // How tall the font is (used to invert the Y axis).
-    ascender: f32,
-    // Translated X and Y positions.
-    offset: (f32, f32),
-    // Font scaling.
-    scale: f32,
-}
-
-impl OutlineBuilder for Outliner<'_> {
-    fn move_to(&mut self, x: f32, y: f32) {
-        let x = x + self.offset.0;
-        let y = self.ascender - (y + self.offset.1);
-        self.path
-            .push(PathOp::Move(Pt::new(x * self.scale, y * self.scale)));
-    }
-
-    fn line_to(&mut self, x: f32, y: f32) {
-        let x = x + self.offset.0;
-        let y = self.ascender - (y + self.offset.1);
-        self.path
-            .push(PathOp::Line(Pt::new(x * self.scale, y * self.scale)));
-    }
+// A parsed face, which gives access to the `ttf_parser` face it was parsed
+// from through `Deref`.
+struct LangFont<'a>(FaceShaper<'a>);
 
-    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
-        let x = x + self.offset.0;
-        let x1 = x1 + self.offset.0;
-        let y = self.ascender - (y + self.offset.1);
-        let y1 = self.ascender - (y1 + self.offset.1);
-        self.path.push(PathOp::Quad(
-            Pt::new(x1 * self.scale, y1 * self.scale),
-            Pt::new(x * self.scale, y * self.scale),
-        ));
-    }
+// A run of text to shape together: its byte range, face and style variant,
+// bidi level and style.
+type Run<'s> = (Range<usize>, (usize, usize), Level, &'s Style);
 
-    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
-        let x = x + self.offset.0;
-        let x1 = x1 + self.offset.0;
-        let x2 = x2 + self.offset.0;
-        let y = self.ascender - (y + self.offset.1);
-        let y1 = self.ascender - (y1 + self.offset.1);
-        let y2 = self.ascender - (y2 + self.offset.1);
-
-        self.path.push(PathOp::Cubic(
-            Pt::new(x1 * self.scale, y1 * self.scale),
-            Pt::new(x2 * self.scale, y2 * self.scale),
-            Pt::new(x * self.scale, y * self.scale),
-        ));
-    }
-
-    fn close(&mut self) {
-        self.path.push(PathOp::Close());
-    }
-}
+// Shaped glyphs of a run, in visual order, and its bidi level.
+type Shaped = (Vec<Placed>, Level);
 
 struct StyledFont<'a> {
     // Buffer associated with this font.
     glyph_buffer: Option<GlyphBuffer>,
-    // Required
-    none: LangFont<'a>,
+    // Style variants, starting with the face passed to `Font::push()`.
+    faces: Vec<(Variant, LangFont<'a>)>,
 }
 
-impl<'a> StyledFont<'a> {
-    fn path(
+impl StyledFont<'_> {
+    fn face(&self, slot: usize) -> &Face<'_> {
+        &self.faces[slot].1 .0
+    }
+
+    // Pick the style variant closest to `wanted`, out of the ones with a
+    // glyph for `c` if given.  Falls back to the first face.
+    fn slot(&self, wanted: &Variant, c: Option<char>) -> usize {
+        let slots: Vec<usize> = (0..self.faces.len())
+            .filter(|slot| {
+                c.is_none_or(|c| self.face(*slot).glyph_index(c).is_some())
+            })
+            .collect();
+        wanted
+            .closest(slots.iter().map(|slot| self.faces[*slot].0))
+            .map_or(0, |i| slots[i])
+    }
+
+    fn path<O: Outline>(
         &self,
-        index: usize,
-        path: &mut Vec<PathOp>,
-        offset: &mut (i32, i32),
+        layout: Layout,
+        baseline: f32,
+        glyph: &Placed,
+        out: &mut O,
     ) {
-        let GlyphInfo {
-            glyph_id,
-            cluster: _,
-            ..
-        } = self.glyph_buffer.as_ref().unwrap().glyph_infos()[index];
-        let GlyphPosition {
-            x_advance,
-            y_advance,
-            x_offset,
-            y_offset,
-            ..
-        } = self.glyph_buffer.as_ref().unwrap().glyph_positions()[index];
-
-        let glyph_id = GlyphId(glyph_id as u16);
-        let scale = (self.none.0.height() as f32).recip();
-
-        // let xy = (xy.0 + x_offset as f32 * scale, -xy.1 - y_offset as f32 * scale);
-        let ascender = self.none.0.ascender() as f32 * scale;
-        let x_offset = x_offset + offset.0;
-        let y_offset = y_offset + offset.1;
-        offset.0 += x_advance;
-        offset.1 += y_advance;
-        let offset = (
-            x_offset as f32,
-            (y_offset - self.none.0.ascender() as i32) as f32,
-        );
+        // Placeholders are drawn by the application.
+        if glyph.inline.is_some() {
+            return;
+        }
+        let mut outliner = Outliner {
+            out,
+            layout,
+            baseline,
+            pos: glyph.pos,
+            scale: glyph.scale,
+            skew: glyph.skew,
+            shift: glyph.shift,
+        };
+        if glyph.bold > 0.0 {
+            let mut embolden = Embolden::default();
+            if self
+                .face(glyph.slot)
+                .outline_glyph(GlyphId(glyph.id), &mut embolden)
+                .is_some()
+            {
+                embolden.finish(glyph.bold, &mut outliner);
+            }
+        } else {
+            self.face(glyph.slot)
+                .outline_glyph(GlyphId(glyph.id), &mut outliner);
+        }
+    }
 
-        self.none.0.outline_glyph(
-            glyph_id,
-            &mut Outliner {
-                path,
-                ascender,
-                scale,
-                offset,
-            },
-        );
+    fn bounds(
+        &self,
+        layout: Layout,
+        baseline: f32,
+        glyph: &Placed,
+    ) -> Option<Rect> {
+        if glyph.inline.is_some() {
+            return None;
+        }
+        let bbox = self
+            .face(glyph.slot)
+            .glyph_bounding_box(GlyphId(glyph.id))?;
+        // Synthetic bold grows the outline on every side.
+        let x = |x: i16, bold: f32| {
+            glyph.pos.0 + (f32::from(x) + bold) * glyph.scale
+        };
+        let y = |y: i16, bold: f32| {
+            glyph.pos.1 + (f32::from(y) + bold) * glyph.scale
+        };
+        let (left, right) =
+            (x(bbox.x_min, -glyph.bold), x(bbox.x_max, glyph.bold));
+        let (bottom, top) =
+            (y(bbox.y_min, -glyph.bold), y(bbox.y_max, glyph.bold));
+        // Synthetic oblique leans the top and bottom in opposite directions.
+        let lean = |y: f32| glyph.skew * (y - glyph.shift);
+        let (a, b) = (lean(bottom), lean(top));
+        Some(Rect::from_corners(
+            (left + a.min(b), layout.y(baseline + bottom)),
+            (right + a.max(b), layout.y(baseline + top)),
+        ))
+    }
+
+    // Find the caret positions inside ligatures: one for each grapheme
+    // boundary within a cluster that's drawn with a single spacing glyph.
+    // Shaping keeps the glyphs of each cluster together.
+    fn carets(&self, text: &str, glyphs: &mut [Placed]) {
+        for cluster in glyphs.chunk_by_mut(|a, b| a.cluster == b.cluster) {
+            let mut spacing =
+                cluster.iter_mut().filter(|glyph| glyph.advance != 0.0);
+            let glyph = match (spacing.next(), spacing.next()) {
+                (Some(glyph), None) => glyph,
+                _ => continue,
+            };
+            let range = glyph.cluster.clone();
+            let boundaries: Vec<usize> = text[range.clone()]
+                .grapheme_indices(true)
+                .skip(1)
+                .map(|(j, _)| range.start + j)
+                .collect();
+            if boundaries.is_empty() {
+                continue;
+            }
+
+            // Use the font's carets, or else split the advance evenly.
+            let n = boundaries.len() + 1;
+            let mut xs: Vec<f32> =
+                match gdef::lig_carets(self.face(glyph.slot), glyph.id) {
+                    Some(carets) if carets.len() == boundaries.len() => carets
+                        .iter()
+                        .map(|x| f32::from(*x) * glyph.scale)
+                        .collect(),
+                    _ => (1..n)
+                        .map(|k| glyph.advance * k as f32 / n as f32)
+                        .collect(),
+                };
+            if glyph.rtl {
+                xs.reverse();
+            }
+            glyph.carets = boundaries.into_iter().zip(xs).collect();
+        }
     }
 }
 
@@ -131,8 +183,16 @@ impl<'a> StyledFont<'a> {
 #[allow(missing_debug_implementations)]
 #[derive(Default)]
 pub struct Font<'a> {
+    #[cfg(feature = "footile")]
     paths: Vec<PathOp>,
     fonts: Vec<StyledFont<'a>>,
+    // Rules that pick faces ahead of the fallback order.
+    rules: Vec<FallbackRule>,
+    layout: Layout,
+    // Glyphs on the current line.
+    glyphs: Vec<Placed>,
+    // Baseline of the current line, measured upwards from the origin.
+    baseline: f32,
 }
 
 impl<'a> Font<'a> {
@@ -141,316 +201,956 @@ impl<'a> Font<'a> {
         Self::default()
     }
 
-    /// Add a TTF or OTF font's glyphs to this `Font`.
+    /// Add a TTF or OTF font's glyphs to this `Font`.  Fonts pushed later are
+    /// used as fallbacks for characters missing from earlier ones.
+    ///
+    /// WOFF and WOFF2 files have to be decoded with
+    /// [`decode_woff()`](crate::decode_woff) first: the `Font` only borrows
+    /// `font_data`, so it has nowhere to keep decoded data.  A
+    /// [`FontDatabase`](crate::FontDatabase) decodes the WOFF files it finds
+    /// itself.
     pub fn push<B: Into<&'a [u8]>>(mut self, font_data: B) -> Option<Self> {
-        let font_data = font_data.into();
-        let face = (
-            Face::from_slice(font_data, 0).ok()?,
-            FaceShaper::from_slice(font_data, 0)?,
+        self.add(font_data.into(), 0)?;
+        Some(self)
+    }
+
+    /// Add a style variant (like bold or italic) of the font last added with
+    /// [`Font::push()`], to use for text with a [`Style`] that asks for it.
+    /// Its weight, slant and stretch are read from its `OS/2` table.  Returns
+    /// `None` if no font has been pushed yet.
+    pub fn push_variant<B: Into<&'a [u8]>>(
+        mut self,
+        font_data: B,
+    ) -> Option<Self> {
+        self.add_variant(font_data.into(), 0)?;
+        Some(self)
+    }
+
+    /// Insert a TTF or OTF font at position `index` in the fallback order,
+    /// moving the fonts after it back.  Returns `None` (leaving this `Font`
+    /// unchanged) if `index` is past the end or the font can't be parsed.
+    ///
+    /// [`FallbackRule`]s and the line laid out last are updated to match, but
+    /// face indices in [`Style`]s and [`Paragraph`]s laid out before aren't
+    /// (see [`Font::render_paragraph()`]).
+    pub fn insert<B: Into<&'a [u8]>>(
+        &mut self,
+        index: usize,
+        font_data: B,
+    ) -> Option<()> {
+        if index > self.fonts.len() {
+            return None;
+        }
+        let face = load(font_data.into(), 0)?;
+        self.fonts.insert(
+            index,
+            StyledFont {
+                faces: vec![face],
+                glyph_buffer: None,
+            },
         );
-        let none = LangFont(face.0, face.1);
+        self.renumber(|face| Some(if face >= index { face + 1 } else { face }));
+        Some(())
+    }
 
+    /// Remove the font at position `index` in the fallback order, along with
+    /// its style variants, moving the fonts after it forward.  Returns `None`
+    /// if there's no font at `index`.
+    ///
+    /// The face is dropped from [`FallbackRule`]s, and the line laid out last
+    /// is cleared if it used the face.  [`Paragraph`]s laid out before aren't
+    /// updated (see [`Font::render_paragraph()`]).
+    pub fn remove(&mut self, index: usize) -> Option<()> {
+        if index >= self.fonts.len() {
+            return None;
+        }
+        self.fonts.remove(index);
+        self.renumber(|face| match face {
+            _ if face == index => None,
+            _ if face > index => Some(face - 1),
+            _ => Some(face),
+        });
+        Some(())
+    }
+
+    /// Swap the fonts at positions `a` and `b` in the fallback order.
+    /// Returns `None` if there's no font at either position.
+    ///
+    /// [`FallbackRule`]s and the line laid out last are updated to match, but
+    /// [`Paragraph`]s laid out before aren't (see
+    /// [`Font::render_paragraph()`]).
+    pub fn swap(&mut self, a: usize, b: usize) -> Option<()> {
+        if a >= self.fonts.len() || b >= self.fonts.len() {
+            return None;
+        }
+        self.fonts.swap(a, b);
+        self.renumber(|face| match face {
+            _ if face == a => Some(b),
+            _ if face == b => Some(a),
+            _ => Some(face),
+        });
+        Some(())
+    }
+
+    /// Replace the font at position `index` in the fallback order (and its
+    /// style variants) with another TTF or OTF font.  Returns `None` (leaving
+    /// this `Font` unchanged) if there's no font at `index` or the new one
+    /// can't be parsed.
+    ///
+    /// [`FallbackRule`]s keep using the same position, and the line laid out
+    /// last is cleared if it used the old font.  [`Paragraph`]s laid out before
+    /// aren't updated (see [`Font::render_paragraph()`]).
+    pub fn replace<B: Into<&'a [u8]>>(
+        &mut self,
+        index: usize,
+        font_data: B,
+    ) -> Option<()> {
+        if index >= self.fonts.len() {
+            return None;
+        }
+        let face = load(font_data.into(), 0)?;
+        self.fonts[index] = StyledFont {
+            faces: vec![face],
+            glyph_buffer: None,
+        };
+        if self.glyphs.iter().any(|glyph| glyph.face == index) {
+            self.glyphs.clear();
+        }
+        Some(())
+    }
+
+    // Move the faces that fallback rules and the current line refer to, after
+    // the fallback order changed.  `None` means the face was removed.
+    fn renumber<F: Fn(usize) -> Option<usize>>(&mut self, new: F) {
+        for rule in self.rules.iter_mut() {
+            rule.faces =
+                rule.faces.iter().filter_map(|face| new(*face)).collect();
+        }
+        if self.glyphs.iter().any(|glyph| new(glyph.face).is_none()) {
+            self.glyphs.clear();
+        }
+        for glyph in self.glyphs.iter_mut() {
+            glyph.face = new(glyph.face).unwrap_or(glyph.face);
+        }
+    }
+
+    // Add face number `index` of a font file (more than 0 only in font
+    // collections) as a new fallback font.
+    pub(crate) fn add(
+        &mut self,
+        font_data: &'a [u8],
+        index: u32,
+    ) -> Option<()> {
         self.fonts.push(StyledFont {
-            none,
+            faces: vec![load(font_data, index)?],
             glyph_buffer: None,
         });
-        Some(self)
+        Some(())
+    }
+
+    // Add face number `index` of a font file as a style variant of the last
+    // fallback font.
+    pub(crate) fn add_variant(
+        &mut self,
+        font_data: &'a [u8],
+        index: u32,
+    ) -> Option<()> {
+        let face = load(font_data, index)?;
+        self.fonts.last_mut()?.faces.push(face);
+        Some(())
+    }
+
+    /// Get the strings in the `name` table of each face of the fallback font
+    /// at `face` (in the order they were pushed): first the face added with
+    /// [`Font::push()`], then its style variants.
+    pub fn names(&self, face: usize) -> impl Iterator<Item = Names<'_>> {
+        self.fonts
+            .get(face)
+            .into_iter()
+            .flat_map(|font| font.faces.iter())
+            .map(|(variant, face)| Names::new(&face.0, *variant))
+    }
+
+    /// Get the copyright and license of every face (in the order they were
+    /// pushed, each followed by its style variants), for third-party notices.
+    /// Bundled faces (from `monospace_font()` or `normal_font()`) come
+    /// with their full license text, and other faces with what their `name`
+    /// table says.
+    pub fn attributions(&self) -> Vec<Attribution> {
+        self.fonts
+            .iter()
+            .flat_map(|font| font.faces.iter())
+            .map(|(variant, face)| {
+                let data = face.0.raw_face().data;
+                Attribution::new(data, Names::new(&face.0, *variant))
+            })
+            .collect()
+    }
+
+    /// Get the code points that the fallback font at `face` has glyphs for,
+    /// as sorted ranges, from the `cmap` table of the face added with
+    /// [`Font::push()`].
+    pub fn coverage(&self, face: usize) -> Vec<RangeInclusive<u32>> {
+        self.fonts
+            .get(face)
+            .map_or_else(Vec::new, |font| coverage::ranges(font.face(0)))
+    }
+
+    /// Find the characters of `text` that no face has a glyph for, with
+    /// their byte offsets.  Characters that aren't drawn with glyphs of their
+    /// own (like control characters and zero width joiners) are never
+    /// missing.
+    pub fn missing<'t>(
+        &'t self,
+        text: &'t str,
+    ) -> impl Iterator<Item = (usize, char)> + 't {
+        text.char_indices().filter(move |(_, c)| {
+            !coverage::invisible(*c) && self.fallback(*c, None, None).is_none()
+        })
+    }
+
+    /// Split `text` into runs that are each drawn with one face, the same
+    /// way as when rendering with the default style, and get the index of
+    /// the face for each (or `None` where no face has the glyphs).
+    pub fn face_runs(&self, text: &str) -> Vec<(Range<usize>, Option<usize>)> {
+        // Characters that aren't drawn with glyphs of their own join the run
+        // before them (or else after them).
+        let mut chars: Vec<(Range<usize>, Option<Option<usize>>)> = Vec::new();
+        let mut current = None;
+        for (i, c) in text.char_indices() {
+            let face = if coverage::invisible(c) {
+                None
+            } else {
+                current = self.fallback(c, current, None);
+                Some(current)
+            };
+            chars.push((i..i + c.len_utf8(), face));
+        }
+        let mut previous = None;
+        for (_, face) in chars.iter_mut() {
+            match face {
+                Some(face) => previous = Some(*face),
+                None => *face = previous,
+            }
+        }
+        let mut next = None;
+        for (_, face) in chars.iter_mut().rev() {
+            match face {
+                Some(face) => next = Some(*face),
+                None => *face = next,
+            }
+        }
+
+        let mut runs: Vec<(Range<usize>, Option<usize>)> = Vec::new();
+        for (range, face) in chars {
+            let face = face.flatten();
+            match runs.last_mut() {
+                Some(run) if run.1 == face => run.0.end = range.end,
+                _ => runs.push((range, face)),
+            }
+        }
+        runs
+    }
+
+    /// Find the face that best fits a [`Query`], following the CSS Fonts
+    /// Level 4 matching rules.  The first family in the query with any faces
+    /// is picked (every face is considered if there are no families), and
+    /// then the closest face by stretch, slant and weight.  Family names are
+    /// read from the `name` table, and style from the `OS/2` table.  Returns
+    /// `None` if none of the families have been pushed.
+    ///
+    /// Setting the same weight, slant and stretch on a [`Style`] along with
+    /// [`Style::face()`] draws text with the found face.
+    pub fn find(&self, query: &Query) -> Option<Match> {
+        let faces: Vec<(usize, usize)> = self
+            .fonts
+            .iter()
+            .enumerate()
+            .flat_map(|(face, font)| {
+                (0..font.faces.len()).map(move |slot| (face, slot))
+            })
+            .collect();
+        let variant =
+            |(face, slot): (usize, usize)| self.fonts[face].faces[slot].0;
+        let (face, slot) = query::find(
+            query,
+            &faces,
+            |(face, slot), family| {
+                query::families(self.fonts[face].face(slot))
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(family))
+            },
+            variant,
+        )?;
+        Some(Match {
+            face,
+            variant: variant((face, slot)),
+        })
+    }
+
+    /// Set the coordinate system used for paths and metrics.
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.layout = layout;
+    }
+
+    /// Set the rules that pick faces for some characters ahead of the order
+    /// they were pushed in.  Rules are tried in order, and a face set on a
+    /// [`Style`] with [`Style::face()`] still comes first.
+    pub fn set_fallback_rules(&mut self, rules: Vec<FallbackRule>) {
+        self.rules = rules;
+    }
+
+    /// Get the rules that pick faces ahead of the order they were pushed in.
+    pub fn fallback_rules(&self) -> &[FallbackRule] {
+        &self.rules
+    }
+
+    /// Get the coordinate system used for paths and metrics.
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Get the vertical metrics of a line of text, in the same coordinates as
+    /// the paths generated by [`Font::render()`].  Returns `None` if no fonts
+    /// have been pushed.
+    pub fn metrics(&self) -> Option<LineMetrics> {
+        let face = self.fonts.first()?.face(0);
+        let scale = self.layout.scale(face);
+        Some(self.layout.metrics(
+            f32::from(face.ascender()) * scale,
+            f32::from(face.descender()) * scale,
+            f32::from(face.line_gap()) * scale,
+        ))
     }
 
     /// Render some text.  Returns an iterator and index within the `&str` where
     /// rendering stopped.
     ///  - `text`: text to render.
     ///  - `row`: x (Left/Right align) or y (Up/Down align) offset where to stop
-    ///    rendering.
+    ///    rendering, in the same units as the output (see [`Layout::size()`]).
+    ///    Rendering stops before the word that doesn't fit, or within a word
+    ///    too wide for `row`.
     ///
     ///  Returns an iterator which generates the path from characters (see
     ///  [`TextPathIterator`]) and a number indicating how many characters are
-    ///  leftover (not rendered).
+    ///  leftover (not rendered).  The iterator is empty if no fonts have been
+    ///  pushed.  Requires feature = "footile", enabled by default.
+    #[cfg(feature = "footile")]
     pub fn render<'b>(
         &'b mut self,
         text: &str,
         row: f32,
     ) -> (TextPathIterator<'a, 'b>, Option<usize>) {
-        let row: f64 = row.into();
-        let row: i32 = (u16::MAX as f64 * row) as i32;
-        let mut text = text;
+        let left_over = self.shape(text, &[], 0, row);
 
-        // Look for newlines and spaces to handle specially.
-        let mut left_over = None;
-        for (i, c) in text.char_indices() {
-            match c {
-                // ' ' => last_space = i,
-                '\n' => {
-                    left_over = Some(i + 1);
-                    text = &text[..i];
-                    break;
-                }
-                _ => {}
-            }
-        }
+        // Return iterator over PathOps and index to start on next call.
+        (
+            TextPathIterator {
+                fontc: self,
+                index: 0,
+                path_i: 0,
+            },
+            left_over,
+        )
+    }
 
-        // Replace glyph buffer using text.
-        // FIXME: Currently only using first font.
-        self.fonts[0].glyph_buffer = Some({
-            let mut unicode_buffer =
-                if let Some(buf) = self.fonts[0].glyph_buffer.take() {
-                    buf.clear()
-                } else {
-                    UnicodeBuffer::new()
-                };
-            unicode_buffer.push_str(text);
-            rustybuzz::shape(&self.fonts[0].none.1, &[], unicode_buffer)
-        });
+    /// Render some text, one glyph at a time.  Same as [`Font::render()`],
+    /// except that the iterator yields each glyph's path together with which
+    /// glyph and which part of `text` it came from (see [`GlyphPath`]).
+    /// Requires feature = "footile", enabled by default.
+    #[cfg(feature = "footile")]
+    pub fn glyphs<'b>(
+        &'b mut self,
+        text: &str,
+        row: f32,
+    ) -> (GlyphPathIterator<'a, 'b>, Option<usize>) {
+        let left_over = self.shape(text, &[], 0, row);
 
-        // Pass over glyphs, looking for where to stop.
-        let positions = self.fonts[0]
-            .glyph_buffer
-            .as_ref()
-            .unwrap()
-            .glyph_positions();
-        let infos = self.fonts[0].glyph_buffer.as_ref().unwrap().glyph_infos();
-        let mut until = positions.len();
-        'crop: for (index, glyph) in positions.iter().enumerate() {
-            if glyph.x_offset > row {
-                left_over = Some(infos[index].cluster as usize);
-                until = index;
-                break 'crop;
-            }
+        // Build every glyph's path up front, so they can be borrowed.
+        self.paths.clear();
+        let mut ends = Vec::with_capacity(self.glyphs.len());
+        for glyph in self.glyphs.iter() {
+            self.fonts[glyph.face].path(
+                self.layout,
+                self.baseline,
+                glyph,
+                &mut self.paths,
+            );
+            ends.push(self.paths.len());
         }
 
-        // Return iterator over PathOps and index to start on next call.
         (
-            TextPathIterator {
+            GlyphPathIterator {
                 fontc: self,
-                until,
+                ends,
                 index: 0,
-                path_i: 0,
-                offset: (0, 0),
             },
             left_over,
         )
+    }
+
+    /// Render some text into any [`Outline`] sink, without going through
+    /// footile `PathOp`s.
+    ///  - `text`: text to render.
+    ///  - `row`: x (Left/Right align) or y (Up/Down align) offset where to stop
+    ///    rendering, between words as with [`Font::render()`].
+    ///  - `out`: where to stream the glyph outlines.
+    ///
+    ///  Returns a number indicating how many characters are leftover (not
+    ///  rendered), same as [`Font::render()`].  Nothing is rendered if no
+    ///  fonts have been pushed.
+    pub fn render_into<O: Outline>(
+        &mut self,
+        text: &str,
+        row: f32,
+        out: &mut O,
+    ) -> Option<usize> {
+        let left_over = self.shape(text, &[], 0, row);
+        for glyph in self.glyphs.iter() {
+            self.fonts[glyph.face].path(self.layout, self.baseline, glyph, out);
+        }
+        left_over
+    }
 
-        /*let mut pixel_length = 0.0;
-        let mut last = None;
-        let mut left_over = None;
-        let mut last_space = 0;
+    /// Lay out text into lines no wider than `width`, for hit testing and
+    /// rendering with [`Font::render_paragraph()`].  Lines are broken between
+    /// words (after spaces, and around ideographs), and spaces at the end of a
+    /// line may hang past `width`.  A word wider than `width` is broken
+    /// between clusters.  Placeholders are never broken.
+    pub fn paragraph(&mut self, text: &str, width: f32) -> Paragraph {
+        self.styled_paragraph(text, &[], width)
+    }
 
-        // First Pass: Get pixel length
-        for (i, c) in text.char_indices() {
-            match c {
-                ' ' => last_space = i,
-                '\n' => {
-                    left_over = Some(i + 1);
-                    break;
+    /// Lay out rich text into lines no wider than `width`.  Same as
+    /// [`Font::paragraph()`], except that each span of `spans` (a byte range
+    /// within `text`) is laid out with its own [`Style`].  Text not in any
+    /// span uses the default style, and where spans overlap the last one
+    /// wins.
+    ///
+    /// Lines grow to fit the largest text on them.  Text is shaped across
+    /// span boundaries where the styles allow it, so kerning and ligatures
+    /// aren't broken by changing color.
+    pub fn styled_paragraph(
+        &mut self,
+        text: &str,
+        spans: &[(Range<usize>, Style)],
+        width: f32,
+    ) -> Paragraph {
+        let mut paragraph = Paragraph {
+            text: text.to_string(),
+            spans: spans.to_vec(),
+            width,
+            layout: self.layout,
+            lines: Vec::new(),
+            glyphs: Vec::new(),
+        };
+        let metrics = match self.metrics() {
+            Some(metrics) => metrics,
+            None => return paragraph,
+        };
+
+        // Bottom of the previous line, measured upwards from the origin.
+        let mut bottom = None;
+        // Lay out each paragraph (between newlines) with its own base
+        // direction, shaping it all at once before breaking it into lines.
+        let mut begin = 0;
+        for text in text.split('\n') {
+            let bidi = BidiInfo::new(text, None);
+            let rtl = bidi
+                .paragraphs
+                .first()
+                .is_some_and(|info| info.level.is_rtl());
+            let runs = self.shape_runs(text, spans, begin, &bidi);
+            let breaks = line_breaks(text, &runs, width);
+            let starts = std::iter::once(0).chain(breaks.iter().copied());
+            let ends = breaks.iter().copied().chain(Some(text.len()));
+            let lines = split_lines(runs, &breaks);
+            for (line, (start, end)) in lines.into_iter().zip(starts.zip(ends))
+            {
+                self.place(line);
+
+                // Stack the line below the previous one.
+                let (ascent, descent) = self.extent();
+                let baseline = match bottom {
+                    Some(bottom) => bottom - metrics.line_gap - ascent,
+                    None => self.layout.baseline(ascent, descent),
+                };
+                bottom = Some(baseline + descent);
+
+                // Move the line down, and its clusters into the whole text.
+                let first = paragraph.glyphs.len();
+                for glyph in self.glyphs.drain(..) {
+                    let cluster =
+                        glyph.cluster.start + begin..glyph.cluster.end + begin;
+                    let carets = glyph
+                        .carets
+                        .iter()
+                        .map(|(offset, x)| (offset + begin, *x))
+                        .collect();
+                    paragraph.glyphs.push(Placed {
+                        cluster,
+                        carets,
+                        ..glyph
+                    });
                 }
-                _ => {}
+                let glyphs = first..paragraph.glyphs.len();
+                let ink = paragraph.glyphs[glyphs.clone()]
+                    .iter()
+                    .filter_map(|glyph| {
+                        self.fonts[glyph.face].bounds(
+                            self.layout,
+                            baseline,
+                            glyph,
+                        )
+                    })
+                    .reduce(Rect::union);
+                paragraph.lines.push(Line {
+                    range: begin + start..begin + end,
+                    metrics: LineMetrics {
+                        top: self.layout.y(baseline + ascent),
+                        baseline: self.layout.y(baseline),
+                        bottom: self.layout.y(baseline + descent),
+                        line_gap: metrics.line_gap,
+                    },
+                    ink,
+                    glyphs,
+                    baseline,
+                    rtl,
+                });
             }
+            begin += text.len() + 1;
+        }
 
-            let mut index = 0;
-            let glyph_id = loop {
-                match self.fonts[index].none.0.glyph_index(c) {
-                    Some(v) => break v,
-                    None => {
-                        index += 1;
-                        if index == self.fonts.len() {
-                            // eprintln!("No Glyph for \"{}\" ({})", c, c as u32);
-                            index = 0;
-                            break self.fonts[0]
-                                .none
-                                .0
-                                .glyph_index('�')
-                                .unwrap();
-                        }
-                    }
-                }
-            };
+        paragraph
+    }
 
-            let selected_font = &self.fonts[index].none;
-
-            // Transform font size.
-            let fh = selected_font.0.height() as f32;
-            let font_size = (fh.recip(), fh.recip());
-
-            let advance = match selected_font.0.glyph_hor_advance(glyph_id) {
-                Some(adv) => {
-                    font_size.0
-                        * (f32::from(adv)
-                            + if let Some(last) = last {
-                                selected_font
-                                    .0
-                                    .kerning_subtables()
-                                    .next()
-                                    .unwrap_or_else(Subtable::default)
-                                    .glyphs_kerning(glyph_id, last)
-                                    .unwrap_or(0)
-                                    .into()
-                            } else {
-                                0f32
-                            })
+    /// Render a [`Paragraph`] laid out with this font into any [`Outline`]
+    /// sink.  With the `footile` feature, a `Vec<PathOp>` can be used to
+    /// collect the path.
+    ///
+    /// Glyphs refer to faces by their position in the fallback order, so
+    /// render a paragraph before changing it with [`Font::remove()`] or
+    /// [`Font::replace()`], or lay it out again.  Glyphs whose face or style
+    /// variant no longer exists (or never did, in a paragraph laid out with
+    /// another `Font`) are skipped.
+    pub fn render_paragraph<O: Outline>(
+        &self,
+        paragraph: &Paragraph,
+        out: &mut O,
+    ) {
+        let mut color = None;
+        for line in paragraph.lines() {
+            for glyph in &paragraph.glyphs[line.glyphs.clone()] {
+                let font = match self.font_of(glyph) {
+                    Some(font) => font,
+                    None => continue,
+                };
+                if color != Some(glyph.color) {
+                    color = Some(glyph.color);
+                    out.color(glyph.color);
                 }
-                None => 0.0,
-            };
+                font.path(paragraph.layout, line.baseline, glyph, out);
+            }
+            self.decorate(paragraph, line, &mut color, out);
+        }
+    }
 
-            pixel_length += advance;
+    // Draw the decoration lines of a line of a paragraph, one for each run of
+    // glyphs with the same decoration style.
+    fn decorate<O: Outline>(
+        &self,
+        paragraph: &Paragraph,
+        line: &Line,
+        color: &mut Option<[u8; 4]>,
+        out: &mut O,
+    ) {
+        let default = Style::default();
+        let style = |glyph: &Placed| {
+            Style::at(&paragraph.spans, glyph.cluster.start, &default)
+        };
+        let glyphs = &paragraph.glyphs[line.glyphs.clone()];
+        let mut begin = 0;
+        while begin < glyphs.len() {
+            let run_style = style(&glyphs[begin]);
+            let end = glyphs[begin..]
+                .iter()
+                .position(|glyph| !style(glyph).decorates_like(run_style))
+                .map_or(glyphs.len(), |len| begin + len);
+            let run = &glyphs[begin..end];
+            begin = end;
+            if !run_style.decorated() {
+                continue;
+            }
 
-            // Extends past the width of the bounding box.
-            if pixel_length > row {
-                if last_space != 0 {
-                    left_over = Some(last_space + 1);
-                    break;
-                } else {
-                    left_over = Some(i + 1);
-                    break;
-                }
+            // Decorations follow the span's first choice of face.
+            let face = run_style.face.filter(|face| *face < self.fonts.len());
+            let font = match self.fonts.get(face.unwrap_or(0)) {
+                Some(font) => font,
+                None => return,
+            };
+            let face = font.face(font.slot(&run_style.variant, None));
+            let layout = match run_style.size {
+                Some(size) => paragraph.layout.size(size),
+                None => paragraph.layout,
+            };
+            let scale = layout.scale(face);
+            let baseline = line.baseline + run_style.shift;
+            let kind = run_style.decoration;
+            let span =
+                (run[0].x, run[run.len() - 1].x + run[run.len() - 1].advance);
+            if *color != Some(run_style.color) {
+                *color = Some(run_style.color);
+                out.color(run_style.color);
             }
 
-            last = Some(glyph_id);
+            if run_style.underline {
+                let stroke = Stroke::underline(face, scale);
+                // Waves reach one thickness past the straight line.
+                let reach = match kind {
+                    DecorationStyle::Wavy => stroke.thickness,
+                    _ => 0.0,
+                };
+                let mut skip = InkSkip::new(
+                    layout.y(baseline + stroke.top + reach),
+                    layout.y(baseline + stroke.top - stroke.thickness - reach),
+                );
+                for glyph in run {
+                    if let Some(font) = self.font_of(glyph) {
+                        font.path(layout, line.baseline, glyph, &mut skip);
+                    }
+                }
+                for part in skip.skip(span, stroke.thickness) {
+                    stroke.fill(layout, baseline, part, kind, out);
+                }
+            }
+            if run_style.strikethrough {
+                Stroke::strikethrough(face, scale)
+                    .fill(layout, baseline, span, kind, out);
+            }
+            if run_style.overline {
+                Stroke::overline(face, scale)
+                    .fill(layout, baseline, span, kind, out);
+            }
         }
+    }
 
-        let mut xy = (0.0, 0.0);
-        let mut vertical = false;
+    /// Get the ink bounds of each glyph on the line laid out by the last call
+    /// to [`Font::render()`], [`Font::glyphs()`] or [`Font::render_into()`],
+    /// in the same coordinates as the paths.  Glyphs without an outline
+    /// (like spaces) have no ink, so are `None`.
+    pub fn glyph_ink(&self) -> impl Iterator<Item = Option<Rect>> + '_ {
+        self.glyphs.iter().map(move |glyph| {
+            self.fonts[glyph.face].bounds(self.layout, self.baseline, glyph)
+        })
+    }
 
-        // Second Pass: Get `PathOp`s
-        (
-            TextPathIterator {
-                temp: vec![],
-                back: false,
-                path: CharPathIterator::new(self, xy, vertical),
-            },
-            left_over.unwrap_or_else(|| text.bytes().len()),
-        )*/
+    /// Get the ink bounds of the whole line laid out by the last call to
+    /// [`Font::render()`], [`Font::glyphs()`] or [`Font::render_into()`], in
+    /// the same coordinates as the paths.  Returns `None` if nothing on the
+    /// line has any ink.
+    pub fn ink(&self) -> Option<Rect> {
+        self.glyph_ink().flatten().reduce(Rect::union)
     }
-}
 
-struct CharPathIterator<'a> {
-    // The font to use.
-    font: &'a Font<'a>,
-    // Path of the current character.
-    path: Vec<PathOp>,
-    // Return position for X.
-    xy: (f32, f32),
-    // General direction of the text.
-    direction: Direction,
-    // Last character
-    last: Option<GlyphId>,
-    //
-    vertical: bool,
-    //
-    font_ascender: f32,
-    //
-    font_size: (f32, f32),
-}
+    // Get the font a glyph of a paragraph was laid out with, if its face and
+    // style variant still exist.
+    fn font_of(&self, glyph: &Placed) -> Option<&StyledFont<'a>> {
+        self.fonts
+            .get(glyph.face)
+            .filter(|font| glyph.slot < font.faces.len())
+    }
 
-impl<'a> CharPathIterator<'a> {
-    fn new(font: &'a Font<'a>, xy: (f32, f32), vertical: bool) -> Self {
-        Self {
-            font,
-            path: vec![],
-            xy,
-            direction: Direction::CheckNext,
-            last: None,
-            vertical,
-            font_ascender: 0.0,
-            font_size: (0.0, 0.0),
+    // Get the ascent and descent of the current line (both measured upwards
+    // from the baseline), fitting every glyph on it, and at least the first
+    // face at the layout size.
+    fn extent(&self) -> (f32, f32) {
+        let first = self.fonts[0].face(0);
+        let scale = self.layout.scale(first);
+        let mut ascent = f32::from(first.ascender()) * scale;
+        let mut descent = f32::from(first.descender()) * scale;
+        for glyph in self.glyphs.iter() {
+            if let Some((top, bottom)) = glyph.inline {
+                ascent = ascent.max(top + glyph.shift);
+                descent = descent.min(glyph.shift - bottom);
+                continue;
+            }
+            let face = self.fonts[glyph.face].face(glyph.slot);
+            let top = f32::from(face.ascender()) * glyph.scale + glyph.shift;
+            let bottom =
+                f32::from(face.descender()) * glyph.scale + glyph.shift;
+            ascent = ascent.max(top);
+            descent = descent.min(bottom);
         }
+        (ascent, descent)
+    }
+
+    // Whether a face has a glyph for a character.
+    fn covers(&self, face: usize, c: char) -> bool {
+        self.fonts
+            .get(face)
+            .is_some_and(|font| font.face(0).glyph_index(c).is_some())
     }
 
-    fn set(&mut self, c: char) {
-        /*match c {
-            '\n' => return,
-            _ => {}
+    // Pick a face for a character in text of a language.  The first face
+    // from a matching rule with a glyph for it is used.  Otherwise,
+    // characters shared between scripts (like spaces, punctuation and
+    // combining marks) stay on the current face if possible.  Returns `None`
+    // if no face has a glyph for it.
+    fn fallback(
+        &self,
+        c: char,
+        current: Option<usize>,
+        language: Option<&str>,
+    ) -> Option<usize> {
+        let ruled = self
+            .rules
+            .iter()
+            .filter(|rule| rule.applies(c, language))
+            .flat_map(|rule| rule.faces.iter())
+            .find(|face| self.covers(**face, c));
+        if let Some(face) = ruled {
+            return Some(*face);
         }
+        if let Some(face) = current {
+            let shared =
+                matches!(c.script(), Script::Common | Script::Inherited);
+            if shared && self.covers(face, c) {
+                return Some(face);
+            }
+        }
+        self.fonts
+            .iter()
+            .position(|font| font.face(0).glyph_index(c).is_some())
+    }
 
-        if self.direction == Direction::CheckNext {
-            self.direction = direction(c);
+    // Shape one row of text into the placed glyphs, with the styles of
+    // `spans` (given relative to `begin` bytes before the start of `text`).
+    // Returns the index within the `&str` where rendering stopped, or `None`
+    // (with no glyphs) if no fonts have been pushed.
+    fn shape(
+        &mut self,
+        text: &str,
+        spans: &[(Range<usize>, Style)],
+        begin: usize,
+        row: f32,
+    ) -> Option<usize> {
+        if self.fonts.is_empty() {
+            self.glyphs.clear();
+            return None;
         }
+        // Look for newlines to handle specially.
+        let (text, newline) = match text.find('\n') {
+            Some(i) => (&text[..i], Some(i + 1)),
+            None => (text, None),
+        };
+        let bidi = BidiInfo::new(text, None);
+        let runs = self.shape_runs(text, spans, begin, &bidi);
+        let breaks = line_breaks(text, &runs, row);
+        let line = split_lines(runs, &breaks).into_iter().next();
+        self.place(line.unwrap_or_default());
+        breaks.first().copied().or(newline)
+    }
 
-        let mut index = 0;
-        let glyph_id = loop {
-            match self.font.fonts[index].none.0.glyph_index(c) {
-                Some(v) => break v,
-                None => {
-                    index += 1;
-                    if index == self.font.fonts.len() {
-                        index = 0;
-                        break self.font.fonts[0]
-                            .none
-                            .0
-                            .glyph_index('�')
-                            .unwrap();
-                    }
+    // Shape a paragraph (text without line breaks) into runs with the same
+    // face, bidi level and style, in logical order, with the styles of
+    // `spans` (given relative to `begin` bytes before the start of `text`).
+    fn shape_runs(
+        &mut self,
+        text: &str,
+        spans: &[(Range<usize>, Style)],
+        begin: usize,
+        bidi: &BidiInfo<'_>,
+    ) -> Vec<Shaped> {
+        // Split text into runs with the same direction and style that can be
+        // shaped with the same face.
+        let default = Style::default();
+        let style = |i: usize| Style::at(spans, begin + i, &default);
+        let mut runs: Vec<Run<'_>> = Vec::new();
+        for (i, c) in text.char_indices() {
+            let level = bidi.levels[i];
+            let style = style(i);
+            let current = runs.last().map(|run| run.1 .0);
+            let face = match style.face {
+                _ if style.placeholder.is_some() => 0,
+                Some(face) if self.covers(face, c) => face,
+                _ => self
+                    .fallback(c, current, style.language.as_deref())
+                    .unwrap_or(0),
+            };
+            let slot = self.fonts[face].slot(&style.variant, Some(c));
+            match runs.last_mut() {
+                Some(run)
+                    if run.1 == (face, slot)
+                        && run.2 == level
+                        && run.3.shapes_like(style)
+                        && !(style.placeholder.is_some()
+                            && c == '\u{FFFC}') =>
+                {
+                    run.0.end = i + c.len_utf8()
+                }
+                _ => {
+                    runs.push((i..i + c.len_utf8(), (face, slot), level, style))
                 }
             }
-        };
-
-        let selected_font = &self.font.fonts[index].none;
-
-        self.path.clear();
-
-        /*        if self.bold {
-            self.path.push(PathOp::PenWidth(self.size.0 / 10.0));
-        }*/
-
-        let font_height = selected_font.0.height() as f32;
-        self.font_ascender = selected_font.0.ascender() as f32;
-        self.font_size = (font_height.recip(), font_height.recip());
+        }
 
-        selected_font.0.outline_glyph(glyph_id, self);
+        let mut shaped: Vec<Shaped> = Vec::with_capacity(runs.len());
+        for (range, (face, slot), level, run_style) in runs {
+            let glyphs = match run_style.placeholder {
+                Some((width, ascent, descent)) => vec![Placed {
+                    id: 0,
+                    face,
+                    slot,
+                    cluster: range.clone(),
+                    pos: (0.0, run_style.shift),
+                    x: 0.0,
+                    advance: width,
+                    rtl: level.is_rtl(),
+                    scale: 1.0,
+                    carets: Vec::new(),
+                    color: run_style.color,
+                    shift: run_style.shift,
+                    inline: Some((ascent, descent)),
+                    bold: 0.0,
+                    skew: 0.0,
+                }],
+                None => {
+                    let font = &mut self.fonts[face];
+                    let variant = font.faces[slot].0;
+                    let glyph_buffer = shape::shape(
+                        &font.faces[slot].1 .0,
+                        font.glyph_buffer.take(),
+                        &text[range.clone()],
+                        level.is_rtl(),
+                        run_style.language.as_deref(),
+                        &run_style.shaping_features(),
+                    );
+                    let layout = match run_style.size {
+                        Some(size) => self.layout.size(size),
+                        None => self.layout,
+                    };
+                    let scale = layout.scale(font.face(slot));
+                    let upm = f32::from(font.face(slot).units_per_em());
+                    let mut starts: Vec<usize> = glyph_buffer
+                        .glyph_infos()
+                        .iter()
+                        .map(|info| info.cluster as usize)
+                        .collect();
+                    starts.sort_unstable();
+                    starts.dedup();
 
-        if self.vertical {
-            self.xy.1 += 1.0;
-        } else {
-            let advance = match selected_font.0.glyph_hor_advance(glyph_id) {
-                Some(adv) => {
-                    self.font_size.0
-                        * (f32::from(adv)
-                            + if let Some(last) = self.last {
-                                selected_font
-                                    .0
-                                    .kerning_subtables()
-                                    .next()
-                                    .unwrap_or_else(Subtable::default)
-                                    .glyphs_kerning(glyph_id, last)
-                                    .unwrap_or(0)
-                                    .into()
-                            } else {
-                                0f32
-                            })
+                    let infos = glyph_buffer.glyph_infos();
+                    let positions = glyph_buffer.glyph_positions();
+                    let mut glyphs: Vec<Placed> = infos
+                        .iter()
+                        .zip(positions.iter())
+                        .map(|(info, position)| {
+                            let start = info.cluster as usize;
+                            let end = starts
+                                .iter()
+                                .find(|s| **s > start)
+                                .map_or(range.end, |end| range.start + end);
+                            let style = style(range.start + start);
+                            // Synthetic bold widens spacing glyphs, keeping
+                            // their left side bearing.
+                            let bold = style.bold(&variant) * upm / 2.0;
+                            let advance = position.x_advance as f32 * scale;
+                            let widen = if advance != 0.0 { bold } else { 0.0 };
+                            Placed {
+                                id: info.glyph_id as u16,
+                                face,
+                                slot,
+                                cluster: range.start + start..end,
+                                pos: (
+                                    (position.x_offset as f32 + bold) * scale,
+                                    position.y_offset as f32 * scale
+                                        + style.shift,
+                                ),
+                                x: 0.0,
+                                advance: advance + 2.0 * widen * scale,
+                                rtl: level.is_rtl(),
+                                scale,
+                                carets: Vec::new(),
+                                color: style.color,
+                                shift: style.shift,
+                                inline: None,
+                                bold,
+                                skew: style.skew(layout, &variant),
+                            }
+                        })
+                        .collect();
+                    font.glyph_buffer = Some(glyph_buffer);
+                    font.carets(text, &mut glyphs);
+                    glyphs
                 }
-                None => 0.0,
             };
-            self.xy.0 += advance;
+
+            shaped.push((glyphs, level));
         }
+        shaped
+    }
 
-        self.path.reverse();
+    // Make a line from its runs in logical order: reorder them to visual
+    // order, and place their glyphs.
+    fn place(&mut self, mut shaped: Vec<Shaped>) {
+        self.glyphs.clear();
 
-        self.last = Some(glyph_id);*/
-    }
-}
+        // Baseline is set by the first font.
+        let first = self.fonts[0].face(0);
+        let scale = self.layout.scale(first);
+        self.baseline = self.layout.baseline(
+            f32::from(first.ascender()) * scale,
+            f32::from(first.descender()) * scale,
+        );
 
-impl Iterator for CharPathIterator<'_> {
-    type Item = PathOp;
+        // Reorder runs from logical to visual order (Unicode Bidi rule L2).
+        let highest = shaped.iter().map(|run| run.1).max();
+        let lowest_odd = shaped
+            .iter()
+            .map(|run| run.1)
+            .filter(|level| level.is_rtl())
+            .min();
+        if let (Some(highest), Some(lowest_odd)) = (highest, lowest_odd) {
+            for level in (lowest_odd.number()..=highest.number()).rev() {
+                let mut i = 0;
+                while i < shaped.len() {
+                    let start = i;
+                    while i < shaped.len() && shaped[i].1.number() >= level {
+                        i += 1;
+                    }
+                    shaped[start..i].reverse();
+                    i = i.max(start + 1);
+                }
+            }
+        }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.path.pop()
+        // Place glyphs in visual order.
+        let mut pen = 0.0;
+        for (glyphs, _) in shaped {
+            for mut glyph in glyphs {
+                glyph.x = pen;
+                glyph.pos.0 += pen;
+                pen += glyph.advance;
+                self.glyphs.push(glyph);
+            }
+        }
     }
 }
 
-/// Iterator that generates a path from characters.
+/// Iterator that generates a path from characters.  Requires feature =
+/// "footile", enabled by default.
+#[cfg(feature = "footile")]
 #[allow(missing_debug_implementations)]
 pub struct TextPathIterator<'a, 'b> {
     // Contains reusable glyph and path buffers.
     fontc: &'b mut Font<'a>,
-    // Index to stop rendering at.
-    until: usize,
     // Current glyph index.
     index: usize,
     // Index for `PathOp`s.
     path_i: usize,
-    // x and y offset.
-    offset: (i32, i32),
 }
 
+#[cfg(feature = "footile")]
 impl Iterator for TextPathIterator<'_, '_> {
     type Item = PathOp;
 
@@ -464,12 +1164,13 @@ impl Iterator for TextPathIterator<'_, '_> {
         // Because no path ops were left, clear buffer for reuse.
         self.fontc.paths.clear();
         self.path_i = 0;
-        // Check for remaining glyphs in the GlyphBuffer.
-        if self.index != self.until {
-            self.fontc.fonts[0].path(
-                self.index,
+        // Check for remaining placed glyphs.
+        if let Some(glyph) = self.fontc.glyphs.get(self.index) {
+            self.fontc.fonts[glyph.face].path(
+                self.fontc.layout,
+                self.fontc.baseline,
+                glyph,
                 &mut self.fontc.paths,
-                &mut self.offset,
             );
             self.index += 1;
             self.next()
@@ -477,84 +1178,332 @@ impl Iterator for TextPathIterator<'_, '_> {
             None
         }
     }
+}
+
+#[cfg(feature = "footile")]
+impl TextPathIterator<'_, '_> {
+    /// Get the ink bounds of the line being rendered, in the same coordinates
+    /// as the paths.  See [`Font::ink()`].
+    pub fn ink(&self) -> Option<Rect> {
+        self.fontc.ink()
+    }
+}
 
-    /*  if let Some(op) = self.path.next() {
-            Some(op)
-        } else if let Some(c) = self.text.peek() {
-            let dir = direction(*c);
-            let dir = if dir == Direction::CheckNext {
-                if self.back {
-                    Direction::RightLeft
-                } else {
-                    Direction::LeftRight
+/// One glyph's path, generated by [`GlyphPathIterator`].  Requires feature =
+/// "footile", enabled by default.
+#[cfg(feature = "footile")]
+#[derive(Debug)]
+pub struct GlyphPath<'b> {
+    /// Glyph ID within its face.
+    pub id: u16,
+    /// Byte range within the rendered text that this glyph was shaped from.
+    /// Ligatures cover several characters, and several glyphs may share one
+    /// cluster (for example, a base letter and its accent).
+    pub cluster: Range<usize>,
+    /// Index of the face (in order of [`Font::push()`]) the glyph is from.
+    pub face: usize,
+    /// Ink bounds of the glyph, or `None` if it has no outline (spaces).
+    pub bounds: Option<Rect>,
+    /// Path of the glyph.
+    pub path: &'b [PathOp],
+}
+
+/// Iterator that generates a path for each glyph.  Requires feature =
+/// "footile", enabled by default.
+#[cfg(feature = "footile")]
+#[allow(missing_debug_implementations)]
+pub struct GlyphPathIterator<'a, 'b> {
+    // Contains the placed glyphs and their paths.
+    fontc: &'b Font<'a>,
+    // End of each glyph's path within the path buffer.
+    ends: Vec<usize>,
+    // Current glyph index.
+    index: usize,
+}
+
+#[cfg(feature = "footile")]
+impl<'b> Iterator for GlyphPathIterator<'_, 'b> {
+    type Item = GlyphPath<'b>;
+
+    fn next(&mut self) -> Option<GlyphPath<'b>> {
+        let fontc = self.fontc;
+        let glyph = fontc.glyphs.get(self.index)?;
+        let start = match self.index {
+            0 => 0,
+            i => self.ends[i - 1],
+        };
+        let end = self.ends[self.index];
+        self.index += 1;
+
+        Some(GlyphPath {
+            id: glyph.id,
+            cluster: glyph.cluster.clone(),
+            face: glyph.face,
+            bounds: fontc.fonts[glyph.face].bounds(
+                fontc.layout,
+                fontc.baseline,
+                glyph,
+            ),
+            path: &fontc.paths[start..end],
+        })
+    }
+}
+
+// Find where to break a paragraph's shaped runs into lines no wider than
+// `row`, as the byte offset each line after the first starts at.  Lines are
+// broken between words, and spaces at the end of a line may hang past `row`.
+// A word too wide for a line of its own is broken between clusters, so every
+// line gets at least one cluster.
+fn line_breaks(text: &str, runs: &[Shaped], row: f32) -> Vec<usize> {
+    let words = word_starts(text);
+    let mut breaks = Vec::new();
+    let mut start = 0;
+    // Width of the line so far, and of the glyphs of the current cluster.
+    let (mut width, mut cluster_width) = (0.0, 0.0);
+    let mut cluster = None;
+    // Start of the last word on the line, and the line's width before it.
+    let mut word: Option<(usize, f32)> = None;
+    for (glyphs, level) in runs {
+        // Glyphs of right-to-left runs are in visual order.
+        let mut logical: Vec<&Placed> = glyphs.iter().collect();
+        if level.is_rtl() {
+            logical.reverse();
+        }
+        for glyph in logical {
+            let at = glyph.cluster.start;
+            if cluster != Some(at) {
+                cluster = Some(at);
+                cluster_width = 0.0;
+                if at != start && words.binary_search(&at).is_ok() {
+                    word = Some((at, width));
                 }
-            } else {
-                dir
-            };
-            if dir == Direction::RightLeft {
-                let c = self.text.next().unwrap();
-                if !self.back {
-                    self.back = true;
+            }
+            let space = text[glyph.cluster.clone()].trim().is_empty();
+            if width + glyph.advance > row && at != start && !space {
+                // Move the last word to the next line, and if it's still too
+                // wide, break it before this cluster.
+                if let Some((at, before)) = word.take() {
+                    start = at;
+                    width -= before;
+                    breaks.push(start);
+                }
+                if width + glyph.advance > row && at != start {
+                    start = at;
+                    width = cluster_width;
+                    breaks.push(start);
+                }
+            }
+            width += glyph.advance;
+            cluster_width += glyph.advance;
+        }
+    }
+    breaks
+}
+
+// Get the byte offsets where a line may break: the start of each word that
+// follows spaces, and around ideographs, which aren't separated by spaces.
+fn word_starts(text: &str) -> Vec<usize> {
+    let ideograph = |c: Option<char>| {
+        c.is_some_and(|c| {
+            matches!(
+                c.script(),
+                Script::Han | Script::Hiragana | Script::Katakana
+            )
+        })
+    };
+    let mut starts = Vec::new();
+    let mut previous: Option<&str> = None;
+    for (i, segment) in text.split_word_bound_indices() {
+        if let Some(previous) = previous {
+            if previous.trim().is_empty()
+                || ideograph(previous.chars().last())
+                || ideograph(segment.chars().next())
+            {
+                starts.push(i);
+            }
+        }
+        previous = Some(segment);
+    }
+    starts
+}
+
+// Split a paragraph's shaped runs into lines at `breaks`.
+fn split_lines(runs: Vec<Shaped>, breaks: &[usize]) -> Vec<Vec<Shaped>> {
+    let mut lines: Vec<Vec<Shaped>> =
+        (0..=breaks.len()).map(|_| Vec::new()).collect();
+    // Index of the run each line's last piece came from.
+    let mut last = vec![None; lines.len()];
+    for (i, (glyphs, level)) in runs.into_iter().enumerate() {
+        for glyph in glyphs {
+            let line = breaks.partition_point(|b| *b <= glyph.cluster.start);
+            match lines[line].last_mut() {
+                Some(piece) if last[line] == Some(i) => piece.0.push(glyph),
+                _ => {
+                    last[line] = Some(i);
+                    lines[line].push((vec![glyph], level));
                 }
-                self.temp.push(c);
-            } else if let Some(c) = self.temp.pop() {
-                self.path.set(c);
-            } else {
-                let c = self.text.next().unwrap();
-                self.path.set(c);
             }
-            self.next()
-        } else if let Some(c) = self.temp.pop() {
-            self.path.set(c);
-            self.next()
-        } else {
-            None
         }
-    }*/
+    }
+    lines
 }
 
-/// Get a monospace font.  Requires feature = "monospace-font", enabled by default.
+// Parse face number `index` of a font file.
+fn load(font_data: &[u8], index: u32) -> Option<(Variant, LangFont<'_>)> {
+    let face = FaceShaper::from_slice(font_data, index)?;
+    Some((Variant::of(&face), LangFont(face)))
+}
+
+/// Get a monospace font, with bold, oblique and bold oblique variants.
+/// Requires feature = "monospace-font", enabled by default.
 #[cfg(feature = "monospace-font")]
 pub fn monospace_font() -> Font<'static> {
-    const FONTA: &[u8] = include_bytes!("font/dejavu/SansMono.ttf");
-    const FONTB: &[u8] = include_bytes!("font/noto/SansDevanagari.ttf");
-    const FONTC: &[u8] = include_bytes!("font/noto/SansHebrew.ttf");
-    const FONTD: &[u8] = include_bytes!("font/droid/SansFallback.ttf");
-
     Font::new()
-        .push(FONTA)
+        .push(bundled::SANS_MONO)
+        .unwrap()
+        .push_variant(bundled::SANS_MONO_BOLD)
+        .unwrap()
+        .push_variant(bundled::SANS_MONO_OBLIQUE)
         .unwrap()
-        .push(FONTB)
+        .push_variant(bundled::SANS_MONO_BOLD_OBLIQUE)
         .unwrap()
-        .push(FONTC)
+        .push(bundled::DEVANAGARI)
         .unwrap()
-        .push(FONTD)
+        .push(bundled::HEBREW)
+        .unwrap()
+        .push(bundled::FALLBACK)
         .unwrap()
 }
 
-/// Get a normal font.  Requires feature = "normal-font".
+/// Get a normal font, with bold, oblique and bold oblique variants.  Requires
+/// feature = "normal-font".
 #[cfg(feature = "normal-font")]
 pub fn normal_font() -> Font<'static> {
-    const FONTA: &[u8] = include_bytes!("font/dejavu/Sans.ttf");
-    const FONTB: &[u8] = include_bytes!("font/noto/SansDevanagari.ttf");
-    const FONTC: &[u8] = include_bytes!("font/noto/SansHebrew.ttf");
-    const FONTD: &[u8] = include_bytes!("font/droid/SansFallback.ttf");
-
     Font::new()
-        .push(FONTA)
+        .push(bundled::SANS)
+        .unwrap()
+        .push_variant(bundled::SANS_BOLD)
+        .unwrap()
+        .push_variant(bundled::SANS_OBLIQUE)
+        .unwrap()
+        .push_variant(bundled::SANS_BOLD_OBLIQUE)
         .unwrap()
-        .push(FONTB)
+        .push(bundled::DEVANAGARI)
         .unwrap()
-        .push(FONTC)
+        .push(bundled::HEBREW)
         .unwrap()
-        .push(FONTD)
+        .push(bundled::FALLBACK)
         .unwrap()
 }
 
 #[cfg(any(feature = "monospace-font", feature = "normal-font"))]
 /// Get a text string of the licenses that must be included in a binary program
 /// for using the font.  Requires either feature = "monospace-font" or feature
-/// = "normal-font"
+/// = "normal-font".  See [`Font::attributions()`] for the licenses of just
+/// the faces in a `Font`.
 pub fn licenses() -> &'static str {
     include_str!("bin-licenses.txt")
 }
+
+#[cfg(all(test, feature = "monospace-font"))]
+mod tests {
+    use super::*;
+
+    // Counts the glyph contours streamed into it.
+    #[derive(Default)]
+    struct Contours(usize);
+
+    impl Outline for Contours {
+        fn move_to(&mut self, _x: f32, _y: f32) {
+            self.0 += 1;
+        }
+        fn line_to(&mut self, _x: f32, _y: f32) {}
+        fn quad_to(&mut self, _cx: f32, _cy: f32, _x: f32, _y: f32) {}
+        fn cubic_to(
+            &mut self,
+            _ax: f32,
+            _ay: f32,
+            _bx: f32,
+            _by: f32,
+            _x: f32,
+            _y: f32,
+        ) {
+        }
+        fn close(&mut self) {}
+    }
+
+    // Get the faces of the glyphs on the current line.
+    fn faces(font: &Font<'_>) -> Vec<usize> {
+        font.glyphs.iter().map(|glyph| glyph.face).collect()
+    }
+
+    // Get the faces of each fallback rule.
+    fn rules(font: &Font<'_>) -> Vec<Vec<usize>> {
+        font.rules.iter().map(|rule| rule.faces.clone()).collect()
+    }
+
+    #[test]
+    fn renumber() {
+        // Sans Mono, Devanagari, Hebrew and the fallback font.
+        let mut font = monospace_font();
+        font.set_fallback_rules(vec![
+            FallbackRule::new(&[1, 2]).script("Hebr"),
+            FallbackRule::new(&[3]).range('\u{4E00}'..='\u{9FFF}'),
+        ]);
+        font.render_into("aש", f32::MAX, &mut Contours::default());
+        assert_eq!(faces(&font), [0, 2]);
+
+        font.insert(1, bundled::HEBREW).unwrap();
+        assert_eq!(rules(&font), [vec![2, 3], vec![4]]);
+        assert_eq!(faces(&font), [0, 3]);
+
+        font.swap(0, 4).unwrap();
+        assert_eq!(rules(&font), [vec![2, 3], vec![0]]);
+        assert_eq!(faces(&font), [4, 3]);
+
+        // Removing an unused face keeps the line.
+        font.remove(2).unwrap();
+        assert_eq!(rules(&font), [vec![2], vec![0]]);
+        assert_eq!(faces(&font), [3, 2]);
+
+        // Removing or replacing a face on the line clears it.
+        font.remove(2).unwrap();
+        assert_eq!(rules(&font), [vec![], vec![0]]);
+        assert!(font.glyphs.is_empty());
+        font.render_into("ש", f32::MAX, &mut Contours::default());
+        assert_eq!(faces(&font), [1]);
+        font.replace(1, bundled::SANS_MONO).unwrap();
+        assert!(font.glyphs.is_empty());
+
+        assert_eq!(font.insert(6, bundled::HEBREW), None);
+        assert_eq!(font.remove(4), None);
+        assert_eq!(font.swap(0, 4), None);
+        assert_eq!(font.replace(4, bundled::HEBREW), None);
+        assert_eq!(rules(&font), [vec![], vec![0]]);
+    }
+
+    #[test]
+    fn stale_paragraph() {
+        // Bold text uses the second style variant of Sans Mono.
+        let spans = [(0..4, Style::new().weight(700))];
+        let paragraph =
+            monospace_font().styled_paragraph("bold שלום", &spans, f32::MAX);
+        let contours = |font: &Font<'_>| {
+            let mut contours = Contours::default();
+            font.render_paragraph(&paragraph, &mut contours);
+            contours.0
+        };
+        let all = contours(&monospace_font());
+        assert!(all > 0);
+
+        // Glyphs whose face no longer exists are skipped.
+        let mut font = monospace_font();
+        font.remove(3).unwrap();
+        font.remove(2).unwrap();
+        assert!(contours(&font) < all);
+        let mut font = monospace_font();
+        font.replace(0, bundled::SANS_MONO).unwrap();
+        assert!(contours(&font) < all);
+        assert_eq!(contours(&Font::new()), 0);
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! Reading ligature caret positions from the `GDEF` table, which ttf-parser
+//! doesn't expose.
+
+use rustybuzz::ttf_parser::{Face, Tag};
+
+/// Get the caret positions within a ligature glyph, in font units from the
+/// glyph origin, in increasing order.  Returns `None` if the font doesn't
+/// list them, or only lists them as contour points (which would need the
+/// glyph's outline to resolve).
+pub(crate) fn lig_carets(face: &Face<'_>, glyph: u16) -> Option<Vec<i16>> {
+    carets(face.raw_face().table(Tag::from_bytes(b"GDEF"))?, glyph)
+}
+
+// Get the caret positions within a ligature glyph from a `GDEF` table.
+fn carets(gdef: &[u8], glyph: u16) -> Option<Vec<i16>> {
+    let list = offset(gdef, 0, 8)?;
+    let index = coverage(gdef, offset(gdef, list, 0)?, glyph)?;
+    if index >= u16(gdef, list + 2)? {
+        return None;
+    }
+    let lig_glyph = offset(gdef, list, 4 + 2 * usize::from(index))?;
+
+    let count = usize::from(u16(gdef, lig_glyph)?);
+    let mut carets = Vec::with_capacity(count);
+    for i in 0..count {
+        let caret = offset(gdef, lig_glyph, 2 + 2 * i)?;
+        match u16(gdef, caret)? {
+            // Format 3 adds a device table, which is ignored.
+            1 | 3 => carets.push(u16(gdef, caret + 2)? as i16),
+            _ => return None,
+        }
+    }
+    carets.sort_unstable();
+    Some(carets)
+}
+
+// Look up a glyph's index in a coverage table.
+fn coverage(data: &[u8], table: usize, glyph: u16) -> Option<u16> {
+    let count = u16(data, table + 2)?;
+    match u16(data, table)? {
+        1 => (0..count).find(|i| {
+            u16(data, table + 4 + 2 * usize::from(*i)) == Some(glyph)
+        }),
+        2 => (0..usize::from(count)).find_map(|i| {
+            let record = table + 4 + 6 * i;
+            let start = u16(data, record)?;
+            let end = u16(data, record + 2)?;
+            if (start..=end).contains(&glyph) {
+                u16(data, record + 4)?.checked_add(glyph - start)
+            } else {
+                None
+            }
+        }),
+        _ => None,
+    }
+}
+
+// Read a non-null 16-bit offset at `at` bytes into `table`, returning the
+// position it points to.
+fn offset(data: &[u8], table: usize, at: usize) -> Option<usize> {
+    match u16(data, table + at)? {
+        0 => None,
+        offset => Some(table + usize::from(offset)),
+    }
+}
+
+// Read a big-endian `u16`.
+fn u16(data: &[u8], at: usize) -> Option<u16> {
+    let bytes = data.get(at..at + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Lay out big-endian 16-bit words.
+    fn table(words: &[u16]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_be_bytes()).collect()
+    }
+
+    #[test]
+    fn coverage_formats() {
+        let glyphs = table(&[1, 4, 3, 5, 7, 9]);
+        let ranges = table(&[2, 2, 5, 7, 0, 20, 21, 3]);
+        for (glyph, in_glyphs, in_ranges) in [
+            (3, Some(0), None),
+            (7, Some(2), Some(2)),
+            (8, None, None),
+            (20, None, Some(3)),
+            (21, None, Some(4)),
+            (22, None, None),
+        ] {
+            assert_eq!(coverage(&glyphs, 0, glyph), in_glyphs, "{}", glyph);
+            assert_eq!(coverage(&ranges, 0, glyph), in_ranges, "{}", glyph);
+        }
+        assert_eq!(coverage(&table(&[3, 1, 5]), 0, 5), None);
+        assert_eq!(coverage(&glyphs[..6], 0, 5), None);
+    }
+
+    #[test]
+    fn ligature_carets() {
+        let gdef = [
+            // Header, with only a ligature caret list.
+            table(&[1, 0, 0, 0, 12, 0]),
+            // Ligature caret list: coverage and two ligature glyphs.
+            table(&[8, 2, 18, 34]),
+            // Coverage of glyphs 10 and 11.
+            table(&[2, 1, 10, 11, 0]),
+            // Glyph 10: a format 1 and a format 3 caret, out of order.
+            table(&[2, 6, 10, 1, 600, 3, 300, 0]),
+            // Glyph 11: a contour point caret.
+            table(&[1, 4, 2, 3]),
+        ]
+        .concat();
+        assert_eq!(carets(&gdef, 10), Some(vec![300, 600]));
+        assert_eq!(carets(&gdef, 11), None);
+        assert_eq!(carets(&gdef, 12), None);
+        // No ligature caret list.
+        assert_eq!(carets(&gdef[..8], 10), None);
+        assert_eq!(carets(&table(&[1, 0, 0, 0, 0, 0]), 10), None);
+    }
+}
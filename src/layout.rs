@@ -0,0 +1,271 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! Layout options and the output coordinate system.
+
+use rustybuzz::ttf_parser::Face;
+use std::ops::Range;
+
+/// Direction of the Y axis in layout output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum YAxis {
+    /// Y increases downwards (raster images, SVG).  Default.
+    Down,
+    /// Y increases upwards (PDF, OpenGL).
+    Up,
+}
+
+/// Where Y = 0 is placed relative to the first line of text.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Origin {
+    /// Top of the line (the ascender).  Default.
+    Top,
+    /// Baseline of the line.
+    Baseline,
+    /// Bottom of the line (the descender).
+    Bottom,
+}
+
+/// What a font size of 1.0 is measured against.
+///
+/// Fonts are designed on a grid of "font units".  To get output coordinates,
+/// they are divided by one of the following lengths (then multiplied by the
+/// layout size, see [`Layout::size()`]).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Em {
+    /// The font's line height: ascender − descender + line gap.  One line of
+    /// text is exactly one size unit tall, no matter which font is used.
+    /// Default.
+    Height,
+    /// The font's units per em (the em square).  This is what CSS
+    /// `font-size` and most other text engines use, so a size of 16 matches
+    /// a browser rendering the same font at `font-size: 16px`.
+    UnitsPerEm,
+}
+
+/// Options for how text is laid out.
+///
+/// ```rust
+/// use fonterator::{Layout, Origin, YAxis};
+///
+/// // Y-up coordinates, with the origin on the baseline.
+/// let layout = Layout::new().y_axis(YAxis::Up).origin(Origin::Baseline);
+/// ```
+///
+/// To lay out text in pixels, the same way a browser does for a given CSS
+/// `font-size`:
+///
+/// ```rust
+/// use fonterator::{Em, Layout};
+///
+/// let layout = Layout::new().em(Em::UnitsPerEm).size(16.0);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Layout {
+    y_axis: YAxis,
+    origin: Origin,
+    em: Em,
+    size: f32,
+    oblique: f32,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            y_axis: YAxis::Down,
+            origin: Origin::Top,
+            em: Em::Height,
+            size: 1.0,
+            oblique: 0.0,
+        }
+    }
+}
+
+impl Layout {
+    /// Create the default layout (Y down, origin at the top).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the direction of the Y axis.
+    pub fn y_axis(mut self, y_axis: YAxis) -> Self {
+        self.y_axis = y_axis;
+        self
+    }
+
+    /// Set where Y = 0 is, relative to the first line.
+    pub fn origin(mut self, origin: Origin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Set what a font size of 1.0 is measured against.
+    pub fn em(mut self, em: Em) -> Self {
+        self.em = em;
+        self
+    }
+
+    /// Set the font size, in output units (pixels if not transformed
+    /// further).  Default is 1.0.
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Slant all text by `angle` degrees (clockwise, around the baseline),
+    /// for when no italic face is available.  Default is 0.  Spans can set
+    /// their own angle with [`Style::oblique()`](crate::Style::oblique), and
+    /// [`Style::OBLIQUE`](crate::Style::OBLIQUE) is a typical angle.  Text
+    /// drawn with an italic or oblique face isn't slanted further.
+    pub fn oblique(mut self, angle: f32) -> Self {
+        self.oblique = angle;
+        self
+    }
+
+    /// Get the angle of synthetic oblique, in degrees.
+    pub(crate) fn oblique_angle(&self) -> f32 {
+        self.oblique
+    }
+
+    /// Get the factor to convert a face's font units to output units.
+    pub(crate) fn scale(&self, face: &Face<'_>) -> f32 {
+        let em = match self.em {
+            Em::Height => f32::from(face.height()),
+            Em::UnitsPerEm => f32::from(face.units_per_em()),
+        };
+        self.size / em
+    }
+
+    /// Get line metrics from an ascent and descent that are already scaled
+    /// (both measured upwards from the baseline, so descent is negative).
+    pub(crate) fn metrics(
+        &self,
+        ascent: f32,
+        descent: f32,
+        line_gap: f32,
+    ) -> LineMetrics {
+        let baseline = self.baseline(ascent, descent);
+        LineMetrics {
+            top: self.y(baseline + ascent),
+            baseline: self.y(baseline),
+            bottom: self.y(baseline + descent),
+            line_gap,
+        }
+    }
+
+    /// Get the baseline position, measured upwards from the origin.
+    pub(crate) fn baseline(&self, ascent: f32, descent: f32) -> f32 {
+        match self.origin {
+            Origin::Top => -ascent,
+            Origin::Baseline => 0.0,
+            Origin::Bottom => -descent,
+        }
+    }
+
+    /// Convert a Y coordinate measured upwards into the output Y axis.
+    pub(crate) fn y(&self, y: f32) -> f32 {
+        match self.y_axis {
+            YAxis::Down => 0.0 - y,
+            YAxis::Up => y,
+        }
+    }
+}
+
+/// Vertical metrics of a line, in layout coordinates.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LineMetrics {
+    /// Y coordinate of the top of the line (the ascender).
+    pub top: f32,
+    /// Y coordinate of the baseline.
+    pub baseline: f32,
+    /// Y coordinate of the bottom of the line (the descender).
+    pub bottom: f32,
+    /// Extra space the font recommends between the bottom of this line and
+    /// the top of the next.
+    pub line_gap: f32,
+}
+
+impl LineMetrics {
+    /// Distance from the top of one line to the top of the next.
+    pub fn height(&self) -> f32 {
+        (self.bottom - self.top).abs() + self.line_gap
+    }
+}
+
+/// An axis-aligned rectangle, in layout coordinates.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rect {
+    /// Smallest X coordinate.
+    pub x: f32,
+    /// Smallest Y coordinate (the top with [`YAxis::Down`], the bottom with
+    /// [`YAxis::Up`]).
+    pub y: f32,
+    /// Width of the rectangle.
+    pub width: f32,
+    /// Height of the rectangle.
+    pub height: f32,
+}
+
+impl Rect {
+    /// Create a rectangle from two opposite corners, in any order.
+    pub(crate) fn from_corners(a: (f32, f32), b: (f32, f32)) -> Self {
+        Self {
+            x: a.0.min(b.0),
+            y: a.1.min(b.1),
+            width: (a.0 - b.0).abs(),
+            height: (a.1 - b.1).abs(),
+        }
+    }
+
+    /// Get the smallest rectangle containing both rectangles.
+    pub fn union(self, other: Self) -> Self {
+        Self::from_corners(
+            (self.x.min(other.x), self.y.min(other.y)),
+            (
+                (self.x + self.width).max(other.x + other.width),
+                (self.y + self.height).max(other.y + other.height),
+            ),
+        )
+    }
+}
+
+// A glyph placed on a line.
+#[derive(Clone, Debug)]
+pub(crate) struct Placed {
+    // Glyph ID within the face.
+    pub(crate) id: u16,
+    // Index of the face within `Font::fonts`.
+    pub(crate) face: usize,
+    // Index of the style variant within the face's fallback level.
+    pub(crate) slot: usize,
+    // Byte range of the text this glyph was shaped from.
+    pub(crate) cluster: Range<usize>,
+    // Position, measured upwards from the baseline (scaled).
+    pub(crate) pos: (f32, f32),
+    // Pen position before the glyph's advance.
+    pub(crate) x: f32,
+    // Horizontal advance (scaled).
+    pub(crate) advance: f32,
+    // Whether the glyph is part of right-to-left text.
+    pub(crate) rtl: bool,
+    // Font scaling.
+    pub(crate) scale: f32,
+    // Byte offsets of grapheme boundaries inside a ligature, with the caret
+    // position of each relative to the pen position (scaled).
+    pub(crate) carets: Vec<(usize, f32)>,
+    // Color of the span the glyph is in.
+    pub(crate) color: [u8; 4],
+    // Baseline shift of the span the glyph is in (already added to `pos`).
+    pub(crate) shift: f32,
+    // Horizontal shear for synthetic oblique (tangent of the angle).
+    pub(crate) skew: f32,
+    // Ascent and descent of an inline placeholder box, which has no glyph.
+    pub(crate) inline: Option<(f32, f32)>,
+    // How far to move the outline outwards for synthetic bold (font units).
+    pub(crate) bold: f32,
+}
@@ -30,9 +30,38 @@
     variant_size_differences
 )]
 
-mod direction;
+mod attribution;
+#[cfg(any(feature = "monospace-font", feature = "normal-font"))]
+mod bundled;
+mod coverage;
+mod database;
+mod decoration;
+mod embolden;
+mod fallback;
 mod font;
-mod shape;
+mod gdef;
+mod layout;
+mod name;
+mod outline;
+mod paragraph;
+mod query;
 mod render;
+mod shape;
+mod style;
+mod variant;
+#[cfg(feature = "woff")]
+mod woff;
 
+pub use attribution::Attribution;
+pub use database::{FaceInfo, FontDatabase};
+pub use fallback::FallbackRule;
 pub use font::*;
+pub use layout::{Em, Layout, LineMetrics, Origin, Rect, YAxis};
+pub use name::{Localized, NameId, Names};
+pub use outline::Outline;
+pub use paragraph::{Caret, Hit, Line, Movement, Paragraph, Placeholder, Side};
+pub use query::{Match, Query};
+pub use style::{DecorationStyle, Style};
+pub use variant::{Slant, Variant};
+#[cfg(feature = "woff")]
+pub use woff::decode_woff;
@@ -0,0 +1,324 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! Reading strings from the `name` table, like family names and licenses.
+
+use crate::variant::Variant;
+use rustybuzz::ttf_parser::{name::Name, name_id, Face, PlatformId, Tag};
+
+/// A kind of string in a face's `name` table.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NameId {
+    /// Copyright notice.
+    Copyright,
+    /// Family name (the typographic family if the face has one, so the
+    /// weights and widths of a family share it).
+    Family,
+    /// Subfamily name, like "Bold Italic" (the typographic subfamily if the
+    /// face has one).
+    Subfamily,
+    /// Full name, like "DejaVu Sans Bold".
+    FullName,
+    /// Version string.
+    Version,
+    /// PostScript name, like "DejaVuSans-Bold".
+    PostScriptName,
+    /// Name of the font vendor.
+    Manufacturer,
+    /// Name of the designer.
+    Designer,
+    /// Description of the typeface.
+    Description,
+    /// Description of the license.
+    License,
+    /// URL of the full license.
+    LicenseUrl,
+}
+
+impl NameId {
+    // Get the name IDs to look for, in order of preference.
+    fn ids(self) -> &'static [u16] {
+        match self {
+            NameId::Copyright => &[name_id::COPYRIGHT_NOTICE],
+            NameId::Family => &[name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY],
+            NameId::Subfamily => {
+                &[name_id::TYPOGRAPHIC_SUBFAMILY, name_id::SUBFAMILY]
+            }
+            NameId::FullName => &[name_id::FULL_NAME],
+            NameId::Version => &[name_id::VERSION],
+            NameId::PostScriptName => &[name_id::POST_SCRIPT_NAME],
+            NameId::Manufacturer => &[name_id::MANUFACTURER],
+            NameId::Designer => &[name_id::DESIGNER],
+            NameId::Description => &[name_id::DESCRIPTION],
+            NameId::License => &[name_id::LICENSE],
+            NameId::LicenseUrl => &[name_id::LICENSE_URL],
+        }
+    }
+}
+
+/// A string from the `name` table in one language.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Localized {
+    /// BCP 47 language tag, like "en-US", or `None` if the font doesn't say
+    /// or uses a language code that isn't known.
+    pub language: Option<String>,
+    /// The string.
+    pub text: String,
+}
+
+/// The strings in the `name` table of a face, from [`Font::names()`].
+///
+/// ```rust
+/// # #[cfg(feature = "monospace-font")] {
+/// use fonterator::NameId;
+///
+/// let font = fonterator::monospace_font();
+/// let names = font.names(0).next().unwrap();
+/// assert_eq!(names.get(NameId::Family).unwrap(), "DejaVu Sans Mono");
+/// # }
+/// ```
+///
+/// [`Font::names()`]: crate::Font::names
+#[derive(Copy, Clone)]
+#[allow(missing_debug_implementations)]
+pub struct Names<'f> {
+    face: &'f Face<'f>,
+    variant: Variant,
+}
+
+impl<'f> Names<'f> {
+    pub(crate) fn new(face: &'f Face<'f>, variant: Variant) -> Self {
+        Self { face, variant }
+    }
+
+    /// Get the weight, slant and stretch of the face.
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Get a string, in US English if there is one, or else in English, or
+    /// else in the first language listed.
+    pub fn get(&self, id: NameId) -> Option<String> {
+        let localized = self.localized(id);
+        let english = |language: &str| {
+            localized
+                .iter()
+                .find(|name| name.language.as_deref() == Some(language))
+        };
+        english("en-US")
+            .or_else(|| english("en"))
+            .or_else(|| localized.first())
+            .map(|name| name.text.clone())
+    }
+
+    /// Get a string in every language the face lists it in.
+    pub fn localized(&self, id: NameId) -> Vec<Localized> {
+        for id in id.ids() {
+            let mut localized: Vec<Localized> = Vec::new();
+            for name in self.face.names() {
+                if name.name_id != *id {
+                    continue;
+                }
+                let name = match decode(&name) {
+                    Some(text) => Localized {
+                        language: language(self.face, &name),
+                        text,
+                    },
+                    None => continue,
+                };
+                if !localized.contains(&name) {
+                    localized.push(name);
+                }
+            }
+            if !localized.is_empty() {
+                return localized;
+            }
+        }
+        Vec::new()
+    }
+}
+
+// Decode a name record: UTF-16BE for Unicode encodings, or plain ASCII from
+// Macintosh Roman.  Returns `None` for other encodings.
+pub(crate) fn decode(name: &Name<'_>) -> Option<String> {
+    if name.is_unicode() {
+        utf16(name.name)
+    } else if name.platform_id == PlatformId::Macintosh
+        && name.encoding_id == 0
+        && name.name.is_ascii()
+    {
+        Some(name.name.iter().map(|c| char::from(*c)).collect())
+    } else {
+        None
+    }
+}
+
+// Decode a UTF-16BE string.
+fn utf16(data: &[u8]) -> Option<String> {
+    let units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+        .collect();
+    String::from_utf16(&units).ok()
+}
+
+// Get the BCP 47 language tag of a name record.
+fn language(face: &Face<'_>, name: &Name<'_>) -> Option<String> {
+    let id = name.language_id;
+    let tag = match name.platform_id {
+        PlatformId::Windows if id >= 0x8000 => return lang_tag(face, id),
+        PlatformId::Windows => WINDOWS
+            .iter()
+            .find(|(windows, _)| *windows == id)
+            .map(|(_, tag)| *tag),
+        PlatformId::Macintosh => MACINTOSH.get(usize::from(id)).cloned(),
+        PlatformId::Unicode if id >= 0x8000 && id != 0xFFFF => {
+            return lang_tag(face, id)
+        }
+        _ => None,
+    };
+    tag.filter(|tag| !tag.is_empty()).map(str::to_string)
+}
+
+// Look up a language tag record, which `name` table format 1 adds for
+// language IDs from 0x8000.
+fn lang_tag(face: &Face<'_>, id: u16) -> Option<String> {
+    tag_record(face.raw_face().table(Tag::from_bytes(b"name"))?, id)
+}
+
+// Look up a language tag record in a `name` table.
+fn tag_record(table: &[u8], id: u16) -> Option<String> {
+    let u16 = |at: usize| {
+        let bytes = table.get(at..at + 2)?;
+        Some(usize::from(u16::from_be_bytes([bytes[0], bytes[1]])))
+    };
+    if u16(0)? != 1 {
+        return None;
+    }
+    let storage = u16(4)?;
+    let tags = 6 + 12 * u16(2)?;
+    let index = usize::from(id.checked_sub(0x8000)?);
+    if index >= u16(tags)? {
+        return None;
+    }
+    let record = tags + 2 + 4 * index;
+    let start = storage + u16(record + 2)?;
+    utf16(table.get(start..start + u16(record)?)?)
+}
+
+// Common Windows language IDs.
+const WINDOWS: &[(u16, &str)] = &[
+    (0x0401, "ar-SA"),
+    (0x0402, "bg-BG"),
+    (0x0403, "ca-ES"),
+    (0x0404, "zh-TW"),
+    (0x0405, "cs-CZ"),
+    (0x0406, "da-DK"),
+    (0x0407, "de-DE"),
+    (0x0408, "el-GR"),
+    (0x0409, "en-US"),
+    (0x040A, "es-ES"),
+    (0x040B, "fi-FI"),
+    (0x040C, "fr-FR"),
+    (0x040D, "he-IL"),
+    (0x040E, "hu-HU"),
+    (0x040F, "is-IS"),
+    (0x0410, "it-IT"),
+    (0x0411, "ja-JP"),
+    (0x0412, "ko-KR"),
+    (0x0413, "nl-NL"),
+    (0x0414, "nb-NO"),
+    (0x0415, "pl-PL"),
+    (0x0416, "pt-BR"),
+    (0x0418, "ro-RO"),
+    (0x0419, "ru-RU"),
+    (0x041A, "hr-HR"),
+    (0x041B, "sk-SK"),
+    (0x041D, "sv-SE"),
+    (0x041E, "th-TH"),
+    (0x041F, "tr-TR"),
+    (0x0420, "ur-PK"),
+    (0x0421, "id-ID"),
+    (0x0422, "uk-UA"),
+    (0x0424, "sl-SI"),
+    (0x0425, "et-EE"),
+    (0x0426, "lv-LV"),
+    (0x0427, "lt-LT"),
+    (0x0429, "fa-IR"),
+    (0x042A, "vi-VN"),
+    (0x042D, "eu-ES"),
+    (0x0439, "hi-IN"),
+    (0x0804, "zh-CN"),
+    (0x0807, "de-CH"),
+    (0x0809, "en-GB"),
+    (0x080A, "es-MX"),
+    (0x080C, "fr-BE"),
+    (0x0813, "nl-BE"),
+    (0x0814, "nn-NO"),
+    (0x0816, "pt-PT"),
+    (0x0C04, "zh-HK"),
+    (0x0C07, "de-AT"),
+    (0x0C09, "en-AU"),
+    (0x0C0A, "es-ES"),
+    (0x0C0C, "fr-CA"),
+    (0x1004, "zh-SG"),
+    (0x1009, "en-CA"),
+    (0x100C, "fr-CH"),
+];
+
+// Macintosh language IDs, by number (empty where unassigned or uncommon).
+const MACINTOSH: &[&str] = &[
+    "en", "fr", "de", "it", "nl", "sv", "es", "da", "pt", "nb", "he", "ja",
+    "ar", "fi", "el", "is", "mt", "tr", "hr", "zh-Hant", "ur", "hi", "th",
+    "ko", "lt", "pl", "hu", "et", "lv", "", "fo", "fa", "ru", "zh-Hans",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Lay out rows of big-endian 16-bit words, followed by UTF-16BE strings.
+    fn table(rows: &[&[u16]], strings: &[&str]) -> Vec<u8> {
+        let strings = strings.iter().flat_map(|string| string.encode_utf16());
+        rows.concat()
+            .into_iter()
+            .chain(strings)
+            .flat_map(u16::to_be_bytes)
+            .collect()
+    }
+
+    #[test]
+    fn language_tags() {
+        let name = table(
+            &[
+                // Format 1, with one name record and storage after the tags.
+                &[1, 1, 28],
+                // Windows, Unicode BMP, first language tag, family name.
+                &[3, 1, 0x8000, 1, 2, 0],
+                // Two language tags.
+                &[2, 10, 2, 14, 12],
+            ],
+            &["A", "en-GB", "zh-Hant"],
+        );
+        for (id, tag) in [
+            (0x8000, Some("en-GB")),
+            (0x8001, Some("zh-Hant")),
+            (0x8002, None),
+            (0x0409, None),
+        ] {
+            let expected = tag.map(str::to_string);
+            assert_eq!(tag_record(&name, id), expected, "{:#X}", id);
+        }
+        // Format 0 has no language tags.
+        let mut format_0 = name.clone();
+        format_0[1] = 0;
+        assert_eq!(tag_record(&format_0, 0x8000), None);
+        // Tags past the end of the table.
+        assert_eq!(tag_record(&name[..36], 0x8001), None);
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! Generic sink for glyph outlines.
+
+#[cfg(feature = "footile")]
+use {footile::PathOp, pointy::Pt};
+
+/// A sink that text layout streams glyph outlines into.
+///
+/// Coordinates are already scaled and positioned, so an implementation only
+/// has to translate each call into its own path representation.  With the
+/// `footile` feature (enabled by default), `Vec<footile::PathOp>` implements
+/// this trait.
+pub trait Outline {
+    /// Begin a new contour at (`x`, `y`).
+    fn move_to(&mut self, x: f32, y: f32);
+
+    /// Straight line to (`x`, `y`).
+    fn line_to(&mut self, x: f32, y: f32);
+
+    /// Quadratic bézier curve with control point (`cx`, `cy`) to (`x`, `y`).
+    fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32);
+
+    /// Cubic bézier curve with control points (`ax`, `ay`) and (`bx`, `by`)
+    /// to (`x`, `y`).
+    fn cubic_to(&mut self, ax: f32, ay: f32, bx: f32, by: f32, x: f32, y: f32);
+
+    /// Close the current contour.
+    fn close(&mut self);
+
+    /// Set the color (sRGB red, green, blue and alpha) of the glyphs that
+    /// follow, from their [`Style`](crate::Style).  Called before the first
+    /// glyph and whenever the color changes.  Does nothing by default.
+    fn color(&mut self, color: [u8; 4]) {
+        let _ = color;
+    }
+}
+
+#[cfg(feature = "footile")]
+impl Outline for Vec<PathOp> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.push(PathOp::Move(Pt::new(x, y)));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.push(PathOp::Line(Pt::new(x, y)));
+    }
+
+    fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32) {
+        self.push(PathOp::Quad(Pt::new(cx, cy), Pt::new(x, y)));
+    }
+
+    fn cubic_to(&mut self, ax: f32, ay: f32, bx: f32, by: f32, x: f32, y: f32) {
+        self.push(PathOp::Cubic(
+            Pt::new(ax, ay),
+            Pt::new(bx, by),
+            Pt::new(x, y),
+        ));
+    }
+
+    fn close(&mut self) {
+        self.push(PathOp::Close());
+    }
+}
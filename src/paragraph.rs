@@ -0,0 +1,822 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! Multi-line text layout, and mapping between positions and text.
+
+use crate::layout::{Layout, LineMetrics, Placed, Rect};
+use crate::style::Style;
+use std::cmp::Ordering;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Which side of a cluster a position is on, in reading order.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Side {
+    /// Before the cluster (left side for left-to-right text, right side for
+    /// right-to-left text).
+    Leading,
+    /// After the cluster (right side for left-to-right text, left side for
+    /// right-to-left text).
+    Trailing,
+}
+
+/// Result of hit testing a [`Paragraph`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Hit {
+    /// Byte offset within the text of the cluster boundary nearest to the
+    /// point.
+    pub offset: usize,
+    /// Index of the line that was hit.
+    pub line: usize,
+    /// Which side of the cluster the point is on.  `Leading` means `offset`
+    /// is the start of the cluster, `Trailing` means it's the end.
+    pub side: Side,
+}
+
+/// Position of the text cursor, from [`Paragraph::caret()`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Caret {
+    /// X coordinate of the caret.
+    pub x: f32,
+    /// Smallest Y coordinate of the caret (the top with
+    /// [`YAxis::Down`](crate::YAxis::Down), the bottom with
+    /// [`YAxis::Up`](crate::YAxis::Up)).
+    pub y: f32,
+    /// Height of the caret (the height of the line, without the line gap).
+    pub height: f32,
+    /// Index of the line the caret is on.
+    pub line: usize,
+}
+
+/// How far to move the text cursor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Movement {
+    /// One grapheme cluster (a user-perceived character).
+    Grapheme,
+    /// One word.
+    Word,
+    /// To the start or end of the line.
+    Line,
+}
+
+/// Where an inline placeholder (see [`Style::placeholder()`]) was laid out.
+///
+/// [`Style::placeholder()`]: crate::Style::placeholder
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placeholder {
+    /// Byte range of the text the placeholder replaced.
+    pub range: Range<usize>,
+    /// Index of the line the placeholder is on.
+    pub line: usize,
+    /// Rectangle to draw the placeholder in.
+    pub rect: Rect,
+}
+
+/// A line of text within a [`Paragraph`].
+#[derive(Clone, Debug)]
+pub struct Line {
+    /// Byte range of the text on this line, not including the line break.
+    pub range: Range<usize>,
+    /// Vertical metrics of this line, in paragraph coordinates.
+    pub metrics: LineMetrics,
+    /// Ink bounds of this line, or `None` if it has no ink.
+    pub ink: Option<Rect>,
+    // Range of glyphs on this line, in visual order.
+    pub(crate) glyphs: Range<usize>,
+    // Baseline, measured upwards from the origin.
+    pub(crate) baseline: f32,
+    // Whether the line's base direction is right-to-left.
+    pub(crate) rtl: bool,
+}
+
+// Horizontal extent of a cluster on a line.
+#[derive(Clone, Debug)]
+pub(crate) struct Cluster {
+    // Byte range of the text.
+    pub(crate) range: Range<usize>,
+    // Smallest X coordinate.
+    pub(crate) left: f32,
+    // Largest X coordinate.
+    pub(crate) right: f32,
+    // Whether the cluster is part of right-to-left text.
+    pub(crate) rtl: bool,
+}
+
+/// Text laid out into lines by [`Font::paragraph()`](crate::Font::paragraph).
+///
+/// Lines are stacked from the origin in the direction of reading, each one
+/// line height (see [`LineMetrics::height()`]) below the last.  Render it with
+/// [`Font::render_paragraph()`](crate::Font::render_paragraph).
+#[derive(Clone, Debug)]
+pub struct Paragraph {
+    pub(crate) text: String,
+    pub(crate) spans: Vec<(Range<usize>, Style)>,
+    pub(crate) width: f32,
+    pub(crate) layout: Layout,
+    pub(crate) lines: Vec<Line>,
+    pub(crate) glyphs: Vec<Placed>,
+}
+
+impl Paragraph {
+    /// Get the lines of the paragraph, from first to last.
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// Get the text that was laid out.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Get the ink bounds of the whole paragraph, or `None` if it has no ink.
+    pub fn ink(&self) -> Option<Rect> {
+        self.lines
+            .iter()
+            .filter_map(|line| line.ink)
+            .reduce(Rect::union)
+    }
+
+    /// Get the inline placeholders, in reading order.
+    pub fn placeholders(&self) -> Vec<Placeholder> {
+        let mut placeholders = Vec::new();
+        for (index, line) in self.lines.iter().enumerate() {
+            for glyph in &self.glyphs[line.glyphs.clone()] {
+                if let Some((ascent, descent)) = glyph.inline {
+                    let y = |y: f32| self.layout.y(line.baseline + y);
+                    placeholders.push(Placeholder {
+                        range: glyph.cluster.clone(),
+                        line: index,
+                        rect: Rect::from_corners(
+                            (glyph.x, y(glyph.shift + ascent)),
+                            (glyph.x + glyph.advance, y(glyph.shift - descent)),
+                        ),
+                    });
+                }
+            }
+        }
+        placeholders.sort_by_key(|placeholder| placeholder.range.start);
+        placeholders
+    }
+
+    /// Map a point in layout coordinates to the nearest cluster boundary.
+    ///
+    /// Points above the first line or below the last line hit the nearest
+    /// line, and points past either end of a line hit the cluster at that
+    /// end.  A cluster is the smallest unit of text that can be hit: for
+    /// example, a ligature or a letter with its accents.  Returns `None` if
+    /// the paragraph has no lines.
+    pub fn hit(&self, x: f32, y: f32) -> Option<Hit> {
+        Some(self.hit_line(self.line_at(y)?, x))
+    }
+
+    /// Get the position of the caret at a byte offset within the text.
+    ///
+    /// An offset where a line wraps is placed at the start of the following
+    /// line.  At the boundary between left-to-right and right-to-left text,
+    /// the caret is placed next to the text in the other direction than the
+    /// line's, so that both offsets where the direction changes can be told
+    /// apart.  Offsets past the end of the text are placed at the end.
+    /// Returns `None` if the paragraph has no lines.
+    pub fn caret(&self, offset: usize) -> Option<Caret> {
+        let offset = offset.min(self.text.len());
+        let line = self.line_of(offset)?;
+        let clusters = self.clusters(&self.lines[line]);
+        let carets = carets(&clusters, self.lines[line].rtl);
+        let metrics = &self.lines[line].metrics;
+
+        Some(Caret {
+            x: caret_x(&clusters, &carets, offset),
+            y: metrics.top.min(metrics.bottom),
+            height: (metrics.bottom - metrics.top).abs(),
+            line,
+        })
+    }
+
+    /// Move the cursor forwards in reading order, returning the new offset.
+    ///
+    /// [`Movement::Word`] moves to the end of the next word, and
+    /// [`Movement::Line`] to the end of the line (or of the next line, if
+    /// already there).
+    pub fn next(&self, offset: usize, movement: Movement) -> usize {
+        let offset = offset.min(self.text.len());
+        match movement {
+            Movement::Grapheme => self
+                .graphemes()
+                .find(|i| *i > offset)
+                .unwrap_or(self.text.len()),
+            Movement::Word => self
+                .words()
+                .map(|word| word.end)
+                .find(|i| *i > offset)
+                .unwrap_or(self.text.len()),
+            Movement::Line => {
+                let end = |line: usize| self.lines[line].range.end;
+                match self.line_of(offset) {
+                    Some(line) if offset < end(line) => end(line),
+                    Some(line) if line + 1 < self.lines.len() => end(line + 1),
+                    _ => self.text.len(),
+                }
+            }
+        }
+    }
+
+    /// Move the cursor backwards in reading order, returning the new offset.
+    ///
+    /// [`Movement::Word`] moves to the start of the previous word, and
+    /// [`Movement::Line`] to the start of the line (or of the previous line,
+    /// if already there).
+    pub fn previous(&self, offset: usize, movement: Movement) -> usize {
+        let offset = offset.min(self.text.len());
+        match movement {
+            Movement::Grapheme => {
+                self.graphemes().filter(|i| *i < offset).last().unwrap_or(0)
+            }
+            Movement::Word => self
+                .words()
+                .map(|word| word.start)
+                .filter(|i| *i < offset)
+                .last()
+                .unwrap_or(0),
+            Movement::Line => {
+                let start = |line: usize| self.lines[line].range.start;
+                match self.line_of(offset) {
+                    Some(line) if offset > start(line) => start(line),
+                    Some(line) if line > 0 => start(line - 1),
+                    _ => 0,
+                }
+            }
+        }
+    }
+
+    /// Move the cursor one step to the left on screen, returning the new
+    /// offset.
+    ///
+    /// Within mixed direction text this follows the visual order, rather
+    /// than the reading order.  [`Movement::Line`] moves to the left end of
+    /// the line.  From the left end of a line, the cursor moves to the end of
+    /// the previous line.
+    pub fn left(&self, offset: usize, movement: Movement) -> usize {
+        self.visual(offset, movement, false)
+    }
+
+    /// Move the cursor one step to the right on screen, returning the new
+    /// offset.
+    ///
+    /// Within mixed direction text this follows the visual order, rather
+    /// than the reading order.  [`Movement::Line`] moves to the right end of
+    /// the line.  From the right end of a line, the cursor moves to the start
+    /// of the next line.
+    pub fn right(&self, offset: usize, movement: Movement) -> usize {
+        self.visual(offset, movement, true)
+    }
+
+    /// Move the cursor to the line above, as close as possible to the X
+    /// coordinate `x` (usually from [`Paragraph::caret()`], remembered across
+    /// repeated moves).  From the first line, moves to the start of the text.
+    pub fn up(&self, offset: usize, x: f32) -> usize {
+        match self.line_of(offset) {
+            Some(line) if line > 0 => self.hit_line(line - 1, x).offset,
+            _ => 0,
+        }
+    }
+
+    /// Move the cursor to the line below, as close as possible to the X
+    /// coordinate `x` (usually from [`Paragraph::caret()`], remembered across
+    /// repeated moves).  From the last line, moves to the end of the text.
+    pub fn down(&self, offset: usize, x: f32) -> usize {
+        match self.line_of(offset) {
+            Some(line) if line + 1 < self.lines.len() => {
+                self.hit_line(line + 1, x).offset
+            }
+            _ => self.text.len(),
+        }
+    }
+
+    /// Get the rectangles covering a byte range of the text, for painting
+    /// selection or search highlights behind it.
+    ///
+    /// Mixed direction text may need more than one rectangle per line.  Rows
+    /// are one line height tall (including the line gap), so that the
+    /// rectangles of consecutive lines touch.  When the range continues past
+    /// the end of a line, its rectangle is extended to the edge of the
+    /// paragraph, and lines in the middle of the range are covered from edge
+    /// to edge.
+    pub fn selection(&self, range: Range<usize>) -> Vec<Rect> {
+        let mut rects = Vec::new();
+        if range.start >= range.end {
+            return rects;
+        }
+        let width = self.full_width();
+        for (index, line) in self.lines.iter().enumerate() {
+            let next = self.lines.get(index + 1).map(|l| l.range.start);
+            let continues = match next {
+                Some(next) => range.start < next && range.end > line.range.end,
+                None => false,
+            };
+            if range.end <= line.range.start
+                || range.start > line.range.end
+                || (range.start == line.range.end && !continues)
+            {
+                continue;
+            }
+
+            // Find the horizontal spans to cover.
+            let mut spans: Vec<(f32, f32)> = Vec::new();
+            if range.start <= line.range.start && continues {
+                spans.push((0.0, width));
+            } else {
+                let clusters = self.clusters(line);
+                for cluster in clusters.iter() {
+                    if cluster.range.start < range.end
+                        && cluster.range.end > range.start
+                    {
+                        spans.push((cluster.left, cluster.right));
+                    }
+                }
+                if continues {
+                    let left = clusters.first().map_or(0.0, |c| c.left);
+                    let right = clusters.last().map_or(0.0, |c| c.right);
+                    spans.push(if line.rtl {
+                        (0.0, left)
+                    } else {
+                        (right, width.max(right))
+                    });
+                }
+            }
+
+            // Merge touching spans, and turn them into rectangles.
+            spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut merged: Vec<(f32, f32)> = Vec::new();
+            for (left, right) in spans {
+                match merged.last_mut() {
+                    Some(last) if left <= last.1 + f32::EPSILON => {
+                        last.1 = last.1.max(right)
+                    }
+                    _ => merged.push((left, right)),
+                }
+            }
+            let top = line.metrics.top;
+            let down = if line.metrics.bottom < top { -1.0 } else { 1.0 };
+            let bottom = top + down * line.metrics.height();
+            for (left, right) in merged {
+                rects.push(Rect::from_corners((left, top), (right, bottom)));
+            }
+        }
+        rects
+    }
+
+    // Get the width of the paragraph for full-width selection rows: the
+    // wrapping width, or the widest line if it doesn't wrap.
+    fn full_width(&self) -> f32 {
+        if self.width.is_finite() {
+            return self.width;
+        }
+        self.lines
+            .iter()
+            .flat_map(|line| self.clusters(line))
+            .map(|cluster| cluster.right)
+            .fold(0.0, f32::max)
+    }
+
+    // Hit test a point at `x` on a line.
+    fn hit_line(&self, line: usize, x: f32) -> Hit {
+        let clusters = self.clusters(&self.lines[line]);
+        let index = match clusters.iter().position(|c| x < c.right) {
+            Some(index) => index,
+            None if !clusters.is_empty() => clusters.len() - 1,
+            None => {
+                return Hit {
+                    offset: self.lines[line].range.start,
+                    line,
+                    side: Side::Leading,
+                }
+            }
+        };
+        let cluster = &clusters[index];
+        let left_half = x < (cluster.left + cluster.right) / 2.0;
+        let (offset, side) = edge(cluster, left_half);
+
+        // Where the direction changes, the offsets on either side of an edge
+        // differ, and only one of them has its caret there.
+        let carets = carets(&clusters, self.lines[line].rtl);
+        let x = if left_half {
+            cluster.left
+        } else {
+            cluster.right
+        };
+        let neighbor = match left_half {
+            true => index.checked_sub(1).and_then(|i| clusters.get(i)),
+            false => clusters.get(index + 1),
+        };
+        let moved = |offset| caret_x(&clusters, &carets, offset) != x;
+        let (offset, side) = match neighbor {
+            Some(neighbor) if moved(offset) => {
+                let (other, other_side) = edge(neighbor, !left_half);
+                if moved(other) {
+                    (offset, side)
+                } else {
+                    (other, other_side)
+                }
+            }
+            _ => (offset, side),
+        };
+
+        Hit { offset, line, side }
+    }
+
+    // Move to the nearest stop to the left or right of the cursor on screen.
+    fn visual(&self, offset: usize, movement: Movement, right: bool) -> usize {
+        let offset = offset.min(self.text.len());
+        let line = match self.line_of(offset) {
+            Some(line) => line,
+            None => return offset,
+        };
+        // Stops displayed on this line: from its start up to where the next
+        // line starts.
+        let start = self.lines[line].range.start;
+        let until = match self.lines.get(line + 1) {
+            Some(next) => next.range.start,
+            None => self.text.len() + 1,
+        };
+        let stops: Vec<usize> = match movement {
+            Movement::Grapheme | Movement::Line => {
+                let end = self.lines[line].range.end;
+                self.text[start..end]
+                    .grapheme_indices(true)
+                    .map(|(i, _)| start + i)
+                    .chain(Some(end))
+                    .filter(|&stop| stop < until)
+                    .collect()
+            }
+            Movement::Word => self
+                .words()
+                .take_while(|word| word.start < until)
+                .flat_map(|word| vec![word.start, word.end])
+                .filter(|stop| (start..until).contains(stop))
+                .collect(),
+        };
+
+        // Order the stops by caret X, and then by offset where carets meet,
+        // so that every stop can be reached.
+        let clusters = self.clusters(&self.lines[line]);
+        let carets = carets(&clusters, self.lines[line].rtl);
+        let key = |stop: usize| (caret_x(&clusters, &carets, stop), stop);
+        let order = |a: &(f32, usize), b: &(f32, usize)| {
+            a.0.total_cmp(&b.0).then(a.1.cmp(&b.1))
+        };
+        let here = key(offset);
+        let direction = if right {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        };
+        let ahead = stops
+            .into_iter()
+            .map(key)
+            .filter(|stop| order(stop, &here) == direction);
+        // The nearest stop, or the farthest for moving to the line's end.
+        let best = if right == (movement == Movement::Line) {
+            ahead.max_by(order)
+        } else {
+            ahead.min_by(order)
+        };
+        match best {
+            Some((_, stop)) => stop,
+            None if right && line + 1 < self.lines.len() => {
+                self.lines[line + 1].range.start
+            }
+            None if !right && line > 0 => self.lines[line - 1].range.end,
+            None => offset,
+        }
+    }
+
+    // Get the index of the line a byte offset is displayed on.
+    fn line_of(&self, offset: usize) -> Option<usize> {
+        if self.lines.is_empty() {
+            return None;
+        }
+        let after = self.lines.partition_point(|l| l.range.start <= offset);
+        Some(after.max(1) - 1)
+    }
+
+    // Get the byte offsets of grapheme cluster boundaries, including the end.
+    fn graphemes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.text
+            .grapheme_indices(true)
+            .map(|(i, _)| i)
+            .chain(Some(self.text.len()))
+    }
+
+    // Get the byte ranges of words (skipping spaces and punctuation).
+    fn words(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.text
+            .split_word_bound_indices()
+            .filter(|(_, word)| word.chars().any(char::is_alphanumeric))
+            .map(|(i, word)| i..i + word.len())
+    }
+
+    // Get the index of the line nearest to a Y coordinate.
+    pub(crate) fn line_at(&self, y: f32) -> Option<usize> {
+        let distance = |line: &Line| {
+            let top = line.metrics.top.min(line.metrics.bottom);
+            let bottom = line.metrics.top.max(line.metrics.bottom);
+            (top - y).max(y - bottom).max(0.0)
+        };
+        let mut nearest = None;
+        for (index, line) in self.lines.iter().enumerate() {
+            let d = distance(line);
+            match nearest {
+                Some((_, nd)) if nd <= d => {}
+                _ => nearest = Some((index, d)),
+            }
+        }
+        nearest.map(|(index, _)| index)
+    }
+
+    // Get the clusters of a line, in visual order.  Ligatures are split into
+    // one cluster per grapheme, at their caret positions.
+    pub(crate) fn clusters(&self, line: &Line) -> Vec<Cluster> {
+        let mut clusters: Vec<(Cluster, Vec<(usize, f32)>)> = Vec::new();
+        for glyph in &self.glyphs[line.glyphs.clone()] {
+            let right = glyph.x + glyph.advance;
+            let carets = glyph.carets.iter().map(|(i, x)| (*i, glyph.x + x));
+            match clusters.last_mut() {
+                Some((cluster, c)) if cluster.range == glyph.cluster => {
+                    cluster.left = cluster.left.min(glyph.x);
+                    cluster.right = cluster.right.max(right);
+                    c.extend(carets);
+                }
+                _ => clusters.push((
+                    Cluster {
+                        range: glyph.cluster.clone(),
+                        left: glyph.x,
+                        right,
+                        rtl: glyph.rtl,
+                    },
+                    carets.collect(),
+                )),
+            }
+        }
+
+        let mut split = Vec::with_capacity(clusters.len());
+        for (cluster, carets) in clusters {
+            if carets.is_empty() {
+                split.push(cluster);
+                continue;
+            }
+            let (leading, trailing) = if cluster.rtl {
+                (cluster.right, cluster.left)
+            } else {
+                (cluster.left, cluster.right)
+            };
+            let mut stops = vec![(cluster.range.start, leading)];
+            stops.extend(carets);
+            stops.push((cluster.range.end, trailing));
+            let mut parts: Vec<Cluster> = stops
+                .windows(2)
+                .map(|pair| Cluster {
+                    range: pair[0].0..pair[1].0,
+                    left: pair[0].1.min(pair[1].1),
+                    right: pair[0].1.max(pair[1].1),
+                    rtl: cluster.rtl,
+                })
+                .collect();
+            if cluster.rtl {
+                parts.reverse();
+            }
+            split.extend(parts);
+        }
+        split
+    }
+}
+
+// Get the offset and side at the left or right edge of a cluster.
+fn edge(cluster: &Cluster, left: bool) -> (usize, Side) {
+    if left != cluster.rtl {
+        (cluster.range.start, Side::Leading)
+    } else {
+        (cluster.range.end, Side::Trailing)
+    }
+}
+
+// Get the caret X coordinate of each cluster boundary on a line, sorted by
+// offset.  Where runs of both directions meet, an offset starts a cluster on
+// one side of the run and ends one on the other, so it's placed next to the
+// text in the other direction than the line's (the `rtl` one).
+fn carets(clusters: &[Cluster], rtl: bool) -> Vec<(usize, f32)> {
+    let mut carets = Vec::with_capacity(2 * clusters.len());
+    for c in clusters {
+        let (leading, trailing) = match c.rtl {
+            true => (c.right, c.left),
+            false => (c.left, c.right),
+        };
+        // Lower comes first: embedded text, then the start of a cluster.
+        let embedded = if c.rtl != rtl { 0 } else { 2 };
+        carets.push((c.range.start, embedded, leading));
+        carets.push((c.range.end, embedded + 1, trailing));
+    }
+    carets.sort_by_key(|(offset, rank, _)| (*offset, *rank));
+    carets.dedup_by_key(|(offset, _, _)| *offset);
+    carets
+        .into_iter()
+        .map(|(offset, _, x)| (offset, x))
+        .collect()
+}
+
+// Get the X coordinate of the caret at a byte offset, from the clusters of the
+// line it's on and their `carets()`.
+fn caret_x(
+    clusters: &[Cluster],
+    carets: &[(usize, f32)],
+    offset: usize,
+) -> f32 {
+    let leading = |c: &Cluster| if c.rtl { c.right } else { c.left };
+    let trailing = |c: &Cluster| if c.rtl { c.left } else { c.right };
+    if let Ok(i) = carets.binary_search_by_key(&offset, |(offset, _)| *offset) {
+        carets[i].1
+    } else if let Some(c) = clusters.iter().find(|c| c.range.contains(&offset))
+    {
+        leading(c)
+    } else if let Some(c) = clusters.iter().find(|c| c.range.end == offset) {
+        trailing(c)
+    } else if let Some(c) = clusters.iter().find(|c| c.range.start > offset) {
+        leading(c)
+    } else {
+        clusters.last().map_or(0.0, trailing)
+    }
+}
+
+#[cfg(all(test, feature = "monospace-font"))]
+mod tests {
+    use super::*;
+    use crate::{monospace_font, Font};
+
+    // Left-to-right, right-to-left, and both ways (each way around).
+    const TEXTS: [&str; 4] =
+        ["abc def", "שלום עולם", "abc שלום def", "שלום abc עולם"];
+
+    // Move from `offset` as far as possible, returning every offset visited.
+    fn walk(offset: usize, step: impl Fn(usize) -> usize) -> Vec<usize> {
+        let mut visited = vec![offset];
+        loop {
+            let next = step(*visited.last().unwrap());
+            if visited.contains(&next) {
+                return visited;
+            }
+            visited.push(next);
+        }
+    }
+
+    #[test]
+    fn visual_movement() {
+        let mut font = monospace_font();
+        for text in TEXTS.iter() {
+            let paragraph = font.paragraph(text, f32::INFINITY);
+            let left_end = paragraph.hit(-1.0, 0.0).unwrap().offset;
+            let right = walk(left_end, |offset| {
+                paragraph.right(offset, Movement::Grapheme)
+            });
+            let mut left = walk(*right.last().unwrap(), |offset| {
+                paragraph.left(offset, Movement::Grapheme)
+            });
+
+            // Every grapheme boundary is visited once, from left to right,
+            // and back.
+            let mut sorted = right.clone();
+            sorted.sort_unstable();
+            assert_eq!(sorted, paragraph.graphemes().collect::<Vec<_>>());
+            left.reverse();
+            assert_eq!(left, right, "{}", text);
+            let x = |offset| paragraph.caret(offset).unwrap().x;
+            assert!(right.windows(2).all(|pair| x(pair[0]) <= x(pair[1])));
+        }
+
+        let paragraph = font.paragraph("abc שלום def", f32::INFINITY);
+        let right =
+            walk(0, |offset| paragraph.right(offset, Movement::Grapheme));
+        assert_eq!(right, [0, 1, 2, 3, 12, 10, 8, 6, 4, 13, 14, 15, 16]);
+        assert_eq!(paragraph.right(0, Movement::Line), 16);
+        assert_eq!(paragraph.left(16, Movement::Line), 0);
+    }
+
+    #[test]
+    fn caret() {
+        let mut font = monospace_font();
+        let paragraph = font.paragraph("שלום עולם", f32::INFINITY);
+        let x = |offset| paragraph.caret(offset).unwrap().x;
+        assert!(x(0) > x(2));
+        assert_eq!(x(17), 0.0);
+        assert_eq!(x(18), 0.0);
+        assert_eq!(x(usize::MAX), 0.0);
+        assert!(Font::new().paragraph("a", 1.0).caret(0).is_none());
+    }
+
+    #[test]
+    fn hit_caret_round_trip() {
+        let mut font = monospace_font();
+        for text in TEXTS.iter() {
+            let paragraph = font.paragraph(text, f32::INFINITY);
+            let line = &paragraph.lines()[0];
+            let clusters = paragraph.clusters(line);
+            let widest = clusters
+                .iter()
+                .map(|cluster| cluster.right - cluster.left)
+                .fold(0.0, f32::max);
+            let right = clusters.last().unwrap().right;
+            for step in 0..=100 {
+                let x = right * step as f32 / 100.0;
+                let hit = paragraph.hit(x, 0.0).unwrap();
+                let caret = paragraph.caret(hit.offset).unwrap();
+                assert!(
+                    (caret.x - x).abs() <= widest / 2.0 + 1e-4,
+                    "{} at {}: {:?} {:?}",
+                    text,
+                    x,
+                    hit,
+                    caret
+                );
+            }
+        }
+    }
+
+    // Get the text of each line of a paragraph.
+    fn texts(paragraph: &Paragraph) -> Vec<&str> {
+        paragraph
+            .lines()
+            .iter()
+            .map(|line| &paragraph.text()[line.range.clone()])
+            .collect()
+    }
+
+    #[test]
+    fn line_breaks() {
+        let mut font = monospace_font();
+        let paragraph = font.paragraph("hello world foo bar baz", 3.0);
+        assert_eq!(
+            texts(&paragraph),
+            ["hello ", "world ", "foo ", "bar ", "baz"]
+        );
+        // Words too wide for a line are broken between clusters.
+        let paragraph = font.paragraph("hello world", 2.0);
+        assert_eq!(texts(&paragraph), ["hel", "lo ", "wor", "ld"]);
+        // Ideographs may break anywhere, but placeholders don't.
+        let width = font.paragraph("漢", f32::INFINITY).caret(3).unwrap().x;
+        let paragraph = font.paragraph("漢字漢字", width * 2.5);
+        assert_eq!(texts(&paragraph), ["漢字", "漢字"]);
+        let paragraph = font.paragraph("x \u{FFFC} y", 1.0);
+        assert_eq!(texts(&paragraph), ["x ", "\u{FFFC} ", "y"]);
+    }
+
+    #[test]
+    fn hit_and_caret_across_lines() {
+        let mut font = monospace_font();
+        for (text, start, rtl) in
+            [("abc def ghi", 4, false), ("שלום עולם טוב", 9, true)]
+        {
+            let paragraph = font.paragraph(text, 2.0);
+            assert_eq!(paragraph.lines().len(), 3, "{}", text);
+            let caret = paragraph.caret(start).unwrap();
+            assert_eq!(caret.line, 1);
+            let first = paragraph.caret(0).unwrap();
+            assert!(caret.y != first.y);
+            // The offset where a line wraps is at the start of the next line.
+            let edge = if rtl { 10.0 } else { -1.0 };
+            let hit = paragraph.hit(edge, caret.y + caret.height / 2.0);
+            assert_eq!(hit.unwrap().offset, start, "{}", text);
+            assert_eq!(paragraph.up(start, caret.x), 0);
+            assert_eq!(paragraph.down(0, first.x), start);
+        }
+    }
+
+    #[test]
+    fn selection_across_lines() {
+        let mut font = monospace_font();
+        let paragraph = font.paragraph("abc def ghi", 2.0);
+        let x = |offset| paragraph.caret(offset).unwrap().x;
+        let rects = paragraph.selection(1..9);
+        assert_eq!(rects.len(), 3);
+        // The first line is covered from the start of the range to the edge
+        // (past it, with the space hanging at its end), the middle line from
+        // edge to edge, and the last one up to the end of the range.
+        let spans: Vec<(f32, f32)> = rects
+            .iter()
+            .map(|rect| (rect.x, rect.x + rect.width))
+            .collect();
+        assert_eq!(spans[0], (x(1), 4.0 * x(1)));
+        assert_eq!(spans[1..], [(0.0, 2.0), (0.0, x(9))]);
+        // Rows touch.
+        for pair in rects.windows(2) {
+            assert!((pair[0].y + pair[0].height - pair[1].y).abs() < 1e-4);
+        }
+        // Within a line.
+        let rects = paragraph.selection(4..7);
+        assert_eq!(rects.len(), 1);
+        assert_eq!((rects[0].x, rects[0].x + rects[0].width), (0.0, x(7)));
+        assert!(paragraph.selection(5..5).is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! Finding faces by family name and style, like CSS font matching.
+
+use crate::name::decode;
+use crate::variant::{Slant, Variant};
+use rustybuzz::ttf_parser::{name_id, Face};
+
+/// A description of the face to look for with [`Font::find()`].
+///
+/// ```rust
+/// use fonterator::{Query, Slant};
+///
+/// // Bold italic DejaVu Sans, or else bold italic Noto Sans.
+/// let query = Query::new()
+///     .family("DejaVu Sans")
+///     .family("Noto Sans")
+///     .weight(700)
+///     .slant(Slant::Italic);
+/// ```
+///
+/// [`Font::find()`]: crate::Font::find
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Query {
+    pub(crate) families: Vec<String>,
+    pub(crate) variant: Variant,
+}
+
+impl Query {
+    /// Create a query for a regular face of any family.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a family name to look for.  Families are tried in the order they
+    /// were added, and names are compared without regard to ASCII case.
+    pub fn family(mut self, name: &str) -> Self {
+        self.families.push(name.to_string());
+        self
+    }
+
+    /// Set the weight, from 1 to 1000: 400 is regular and 700 is bold.
+    pub fn weight(mut self, weight: u16) -> Self {
+        self.variant.weight = weight;
+        self
+    }
+
+    /// Set whether the face should be upright, italic or oblique.
+    pub fn slant(mut self, slant: Slant) -> Self {
+        self.variant.slant = slant;
+        self
+    }
+
+    /// Set the width, as a percentage of the regular width (50 to 200).
+    pub fn stretch(mut self, stretch: f32) -> Self {
+        self.variant.stretch = stretch;
+        self
+    }
+}
+
+/// A face found by [`Font::find()`](crate::Font::find).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Match {
+    /// Index of the fallback font the face belongs to (in the order they were
+    /// pushed), for [`Style::face()`](crate::Style::face).
+    pub face: usize,
+    /// Weight, slant and stretch of the face.
+    pub variant: Variant,
+}
+
+// Pick the face that best fits a query out of `faces`: the closest variant
+// among the faces of the first family in the query that has any, or among
+// all of them if the query has no families.
+pub(crate) fn find<T, F, V>(
+    query: &Query,
+    faces: &[T],
+    has_family: F,
+    variant: V,
+) -> Option<T>
+where
+    T: Copy,
+    F: Fn(T, &str) -> bool,
+    V: Fn(T) -> Variant,
+{
+    let closest = |faces: Vec<T>| {
+        let i = query
+            .variant
+            .closest(faces.iter().map(|face| variant(*face)))?;
+        Some(faces[i])
+    };
+    if query.families.is_empty() {
+        return closest(faces.to_vec());
+    }
+    query.families.iter().find_map(|family| {
+        closest(
+            faces
+                .iter()
+                .cloned()
+                .filter(|face| has_family(*face, family))
+                .collect(),
+        )
+    })
+}
+
+// Get a face's family names (legacy and typographic, in every language).
+pub(crate) fn families(face: &Face<'_>) -> Vec<String> {
+    let mut families: Vec<String> = Vec::new();
+    let names = face.names().into_iter().filter(|name| {
+        name.name_id == name_id::FAMILY
+            || name.name_id == name_id::TYPOGRAPHIC_FAMILY
+    });
+    for name in names.filter_map(|name| decode(&name)) {
+        if !families.contains(&name) {
+            families.push(name);
+        }
+    }
+    families
+}
@@ -1,8 +1,60 @@
-//! Rendering TTF glyphs with footile
+//! Rendering TTF glyphs into an `Outline`
 
-use footile::PathOp;
-use rustybuzz::Face;
+use crate::layout::Layout;
+use crate::outline::Outline;
+use rustybuzz::ttf_parser::OutlineBuilder;
 
-/// Build a path
-pub(crate) fn build_path(path_buffer: &mut Vec<PathOp>, face: &Face<'_>, glyph_x: f32, glyph_y: f32, glyph_id: u16) {
+/// Transforms glyph outlines from font units and streams them into a sink.
+pub(crate) struct Outliner<'a, O: Outline> {
+    // Sink to write out to.
+    pub(crate) out: &'a mut O,
+    // Output coordinate system.
+    pub(crate) layout: Layout,
+    // Baseline position, measured upwards from the origin (scaled).
+    pub(crate) baseline: f32,
+    // Glyph position, measured upwards from the baseline (scaled).
+    pub(crate) pos: (f32, f32),
+    // Font scaling.
+    pub(crate) scale: f32,
+    // Horizontal shear for synthetic oblique (tangent of the angle).
+    pub(crate) skew: f32,
+    // Baseline shift of the glyph, which the shear is around (scaled).
+    pub(crate) shift: f32,
+}
+
+impl<O: Outline> Outliner<'_, O> {
+    fn point(&self, x: f32, y: f32) -> (f32, f32) {
+        let y = self.pos.1 + y * self.scale;
+        let x = self.pos.0 + x * self.scale + self.skew * (y - self.shift);
+        (x, self.layout.y(self.baseline + y))
+    }
+}
+
+impl<O: Outline> OutlineBuilder for Outliner<'_, O> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.point(x, y);
+        self.out.move_to(x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.point(x, y);
+        self.out.line_to(x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (x1, y1) = self.point(x1, y1);
+        let (x, y) = self.point(x, y);
+        self.out.quad_to(x1, y1, x, y);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (x1, y1) = self.point(x1, y1);
+        let (x2, y2) = self.point(x2, y2);
+        let (x, y) = self.point(x, y);
+        self.out.cubic_to(x1, y1, x2, y2, x, y);
+    }
+
+    fn close(&mut self) {
+        self.out.close();
+    }
 }
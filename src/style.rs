@@ -0,0 +1,306 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! Text styles for rich text layout.
+
+use crate::layout::Layout;
+use crate::variant::{Slant, Variant};
+use rustybuzz::{Feature, Tag};
+use std::ops::Range;
+
+/// How decoration lines are drawn (see [`Style::decoration_style()`]).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DecorationStyle {
+    /// A single solid line.  Default.
+    Solid,
+    /// A wavy line, as used for marking spelling errors.
+    Wavy,
+    /// A line of round dots.
+    Dotted,
+    /// A line of short dashes.
+    Dashed,
+}
+
+/// Style of a span of text, for [`Font::styled_paragraph()`].
+///
+/// ```rust
+/// use fonterator::Style;
+///
+/// // Red small caps, using the second pushed face where it has the glyphs.
+/// let style = Style::new()
+///     .face(1)
+///     .color([255, 0, 0, 255])
+///     .feature(*b"smcp", 1);
+/// ```
+///
+/// [`Font::styled_paragraph()`]: crate::Font::styled_paragraph
+#[derive(Clone, Debug, PartialEq)]
+pub struct Style {
+    pub(crate) face: Option<usize>,
+    pub(crate) size: Option<f32>,
+    pub(crate) color: [u8; 4],
+    pub(crate) features: Vec<([u8; 4], u32)>,
+    pub(crate) shift: f32,
+    pub(crate) placeholder: Option<(f32, f32, f32)>,
+    pub(crate) underline: bool,
+    pub(crate) strikethrough: bool,
+    pub(crate) overline: bool,
+    pub(crate) decoration: DecorationStyle,
+    pub(crate) embolden: f32,
+    pub(crate) oblique: Option<f32>,
+    pub(crate) variant: Variant,
+    pub(crate) language: Option<String>,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            face: None,
+            size: None,
+            color: [0, 0, 0, 255],
+            features: Vec::new(),
+            shift: 0.0,
+            placeholder: None,
+            underline: false,
+            strikethrough: false,
+            overline: false,
+            decoration: DecorationStyle::Solid,
+            embolden: 0.0,
+            oblique: None,
+            variant: Variant::REGULAR,
+            language: None,
+        }
+    }
+}
+
+impl Style {
+    /// A typical angle for synthetic oblique, in degrees.
+    pub const OBLIQUE: f32 = 12.0;
+
+    /// Create the default style: the font's fallback order, at the layout
+    /// size, in opaque black.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prefer the face at `index` (in the order they were pushed).  Text it
+    /// doesn't have glyphs for still falls back to the other faces.
+    pub fn face(mut self, index: usize) -> Self {
+        self.face = Some(index);
+        self
+    }
+
+    /// Set the font size, in output units, instead of the layout size (see
+    /// [`Layout::size()`](crate::Layout::size)).
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Set the color, as sRGB red, green, blue and alpha.  Passed to
+    /// [`Outline::color()`](crate::Outline::color) when rendering.
+    pub fn color(mut self, color: [u8; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Set an OpenType feature, for example `(*b"liga", 0)` to turn off
+    /// standard ligatures, or `(*b"smcp", 1)` to turn on small caps.
+    pub fn feature(mut self, tag: [u8; 4], value: u32) -> Self {
+        self.features.push((tag, value));
+        self
+    }
+
+    /// Set the language of the text, as a BCP 47 tag like "ja" or "zh-Hans".
+    /// It's used for picking faces with [`FallbackRule`]s, and for the
+    /// language-specific forms of glyphs in fonts that have them.
+    ///
+    /// [`FallbackRule`]: crate::FallbackRule
+    pub fn language(mut self, language: &str) -> Self {
+        self.language = Some(language.to_string());
+        self
+    }
+
+    /// Shift the baseline up by `shift` output units (down if negative), for
+    /// superscripts and subscripts.
+    pub fn baseline_shift(mut self, shift: f32) -> Self {
+        self.shift = shift;
+        self
+    }
+
+    /// Set the weight, from 1 to 1000: 400 is regular and 700 is bold.  The
+    /// closest style variant of each face is used (see
+    /// [`Font::push_variant()`]), and if it's 500 or lighter when 600 or
+    /// heavier is asked for, it's thickened with synthetic bold.
+    ///
+    /// [`Font::push_variant()`]: crate::Font::push_variant
+    pub fn weight(mut self, weight: u16) -> Self {
+        self.variant.weight = weight;
+        self
+    }
+
+    /// Set whether text is upright, italic or oblique.  If the closest style
+    /// variant of a face is upright, it's slanted with synthetic oblique
+    /// instead, by [`Style::OBLIQUE`] unless [`Style::oblique()`] sets an
+    /// angle.
+    pub fn slant(mut self, slant: Slant) -> Self {
+        self.variant.slant = slant;
+        self
+    }
+
+    /// Set the width, as a percentage of the regular width (50 to 200), to
+    /// pick condensed or expanded style variants.
+    pub fn stretch(mut self, stretch: f32) -> Self {
+        self.variant.stretch = stretch;
+        self
+    }
+
+    /// Make text bolder by thickening every stroke by `strength` ems, for
+    /// when no bold face is available.  Glyph advances grow to match.  A
+    /// strength of `1.0 / 24.0` is close to the step from a regular weight to
+    /// bold.
+    pub fn embolden(mut self, strength: f32) -> Self {
+        self.embolden = strength;
+        self
+    }
+
+    /// Slant text by `angle` degrees (clockwise, around the baseline), for
+    /// when no italic face is available.  Overrides the layout's angle (see
+    /// [`Layout::oblique()`](crate::Layout::oblique)).  Usually
+    /// [`Style::OBLIQUE`].  Text drawn with an italic or oblique face isn't
+    /// slanted further.
+    pub fn oblique(mut self, angle: f32) -> Self {
+        self.oblique = Some(angle);
+        self
+    }
+
+    /// Draw a line under the text.  The position and thickness come from
+    /// the font's `post` table, and the line skips around descenders.
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    /// Draw a line through the text.  The position and thickness come from
+    /// the font's `OS/2` table.
+    pub fn strikethrough(mut self) -> Self {
+        self.strikethrough = true;
+        self
+    }
+
+    /// Draw a line over the text, along the top of the line.
+    pub fn overline(mut self) -> Self {
+        self.overline = true;
+        self
+    }
+
+    /// Set how the span's decoration lines are drawn.  Patterns are lined up
+    /// with the start of the line, so they continue smoothly from one span to
+    /// the next, and are scaled to the thickness of the underline.
+    pub fn decoration_style(mut self, style: DecorationStyle) -> Self {
+        self.decoration = style;
+        self
+    }
+
+    /// Replace the span's text with an inline box, for an image, emoji or
+    /// widget drawn by the application.  The box is `width` wide, reaching
+    /// `ascent` above and `descent` below the baseline (in output units).
+    ///
+    /// Placeholders are never broken across lines, and lines grow to fit
+    /// them.  Find where they end up with [`Paragraph::placeholders()`].  The
+    /// span is usually a single U+FFFC OBJECT REPLACEMENT CHARACTER.  A box
+    /// starts at the span's start and at each U+FFFC in it, covering the text
+    /// up to the next one, so each U+FFFC gets a box of its own.
+    ///
+    /// [`Paragraph::placeholders()`]: crate::Paragraph::placeholders
+    pub fn placeholder(
+        mut self,
+        width: f32,
+        ascent: f32,
+        descent: f32,
+    ) -> Self {
+        self.placeholder = Some((width, ascent, descent));
+        self
+    }
+
+    // Get the horizontal shear for synthetic oblique, from this style or the
+    // layout, for text drawn with a face of `variant`.  Italic and oblique
+    // faces are already slanted, so they're left alone.
+    pub(crate) fn skew(&self, layout: Layout, variant: &Variant) -> f32 {
+        if variant.slant != Slant::Upright {
+            return 0.0;
+        }
+        let angle = match self.oblique {
+            Some(angle) => angle,
+            None if variant.needs_oblique(&self.variant) => Self::OBLIQUE,
+            None => layout.oblique_angle(),
+        };
+        angle.to_radians().tan()
+    }
+
+    // Get the strength of synthetic bold in ems, for text drawn with a face
+    // of `variant`.
+    pub(crate) fn bold(&self, variant: &Variant) -> f32 {
+        if variant.needs_bold(&self.variant) {
+            self.embolden + 1.0 / 24.0
+        } else {
+            self.embolden
+        }
+    }
+
+    // Get the features in the form rustybuzz uses.
+    pub(crate) fn shaping_features(&self) -> Vec<Feature> {
+        self.features
+            .iter()
+            .map(|(tag, value)| Feature::new(Tag::from_bytes(tag), *value, ..))
+            .collect()
+    }
+
+    // Whether text in both styles can be shaped together.  Placeholders can
+    // only be joined with the rest of their own span (and split again at each
+    // U+FFFC by the caller).
+    pub(crate) fn shapes_like(&self, other: &Self) -> bool {
+        if self.placeholder.is_some() || other.placeholder.is_some() {
+            return std::ptr::eq(self, other);
+        }
+        self.size == other.size
+            && self.features == other.features
+            && self.language == other.language
+    }
+
+    // Whether the style draws any decoration lines.
+    pub(crate) fn decorated(&self) -> bool {
+        self.underline || self.strikethrough || self.overline
+    }
+
+    // Whether decoration lines in both styles can be drawn as one.
+    pub(crate) fn decorates_like(&self, other: &Self) -> bool {
+        self.underline == other.underline
+            && self.strikethrough == other.strikethrough
+            && self.overline == other.overline
+            && self.decoration == other.decoration
+            && self.face == other.face
+            && self.variant == other.variant
+            && self.size == other.size
+            && self.color == other.color
+            && self.shift == other.shift
+    }
+
+    // Find the style of the text at a byte offset: the last span containing it.
+    pub(crate) fn at<'s>(
+        spans: &'s [(Range<usize>, Style)],
+        offset: usize,
+        default: &'s Style,
+    ) -> &'s Style {
+        spans
+            .iter()
+            .rev()
+            .find(|(range, _)| range.contains(&offset))
+            .map_or(default, |(_, style)| style)
+    }
+}
@@ -0,0 +1,323 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! Style variants of a face (weight, slant and stretch), and picking the
+//! closest one.
+
+use rustybuzz::ttf_parser::{Face, Width};
+
+/// Whether a face is upright or slanted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Slant {
+    /// Upright.  Default.
+    Upright,
+    /// A cursive italic design.
+    Italic,
+    /// A slanted version of the upright design.
+    Oblique,
+}
+
+/// The weight, slant and stretch of a face, as read from its `OS/2` table,
+/// or asked for by a [`Style`](crate::Style).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Variant {
+    /// Weight, from 1 to 1000: 400 is regular and 700 is bold.
+    pub weight: u16,
+    /// Upright, italic or oblique.
+    pub slant: Slant,
+    /// Width, as a percentage of the regular width (50 to 200).
+    pub stretch: f32,
+}
+
+impl Default for Variant {
+    fn default() -> Self {
+        Self::REGULAR
+    }
+}
+
+impl Variant {
+    /// Regular weight, upright and normal width.
+    pub const REGULAR: Self = Self {
+        weight: 400,
+        slant: Slant::Upright,
+        stretch: 100.0,
+    };
+
+    /// Read the variant of a face from its `OS/2` table.
+    pub(crate) fn of(face: &Face<'_>) -> Self {
+        let slant = if face.is_oblique() {
+            Slant::Oblique
+        } else if face.is_italic() {
+            Slant::Italic
+        } else {
+            Slant::Upright
+        };
+        let stretch = match face.width() {
+            Width::UltraCondensed => 50.0,
+            Width::ExtraCondensed => 62.5,
+            Width::Condensed => 75.0,
+            Width::SemiCondensed => 87.5,
+            Width::Normal => 100.0,
+            Width::SemiExpanded => 112.5,
+            Width::Expanded => 125.0,
+            Width::ExtraExpanded => 150.0,
+            Width::UltraExpanded => 200.0,
+        };
+        Self {
+            weight: face.weight().to_number(),
+            slant,
+            stretch,
+        }
+    }
+
+    /// Pick the closest of some variants to this one, following the CSS
+    /// Fonts Level 4 matching rules: narrow down by stretch first, then by
+    /// slant, then by weight.  Returns `None` if there are no variants.
+    pub(crate) fn closest<I>(&self, variants: I) -> Option<usize>
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        let mut candidates: Vec<(usize, Self)> =
+            variants.into_iter().enumerate().collect();
+        keep_best(&mut candidates, |v| stretch_rank(self.stretch, v.stretch));
+        keep_best(&mut candidates, |v| slant_rank(self.slant, v.slant));
+        keep_best(&mut candidates, |v| weight_rank(self.weight, v.weight));
+        candidates.first().map(|(index, _)| *index)
+    }
+
+    // Whether a face of this variant needs synthetic bold to look like
+    // `wanted`.
+    pub(crate) fn needs_bold(&self, wanted: &Self) -> bool {
+        wanted.weight >= 600 && self.weight <= 500
+    }
+
+    // Whether a face of this variant needs synthetic oblique to look like
+    // `wanted`.
+    pub(crate) fn needs_oblique(&self, wanted: &Self) -> bool {
+        wanted.slant != Slant::Upright && self.slant == Slant::Upright
+    }
+}
+
+// Keep only the candidates with the lowest rank.
+fn keep_best<F>(candidates: &mut Vec<(usize, Variant)>, rank: F)
+where
+    F: Fn(&Variant) -> (u8, f32),
+{
+    let best = candidates
+        .iter()
+        .map(|(_, v)| rank(v))
+        .min_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    if let Some(best) = best {
+        candidates.retain(|(_, v)| rank(v) == best);
+    }
+}
+
+// Rank a stretch: narrower is preferred for normal or narrower widths, and
+// wider is preferred for wider widths.  Lower is better.
+fn stretch_rank(wanted: f32, stretch: f32) -> (u8, f32) {
+    let distance = (stretch - wanted).abs();
+    let preferred = if wanted <= 100.0 {
+        stretch <= wanted
+    } else {
+        stretch >= wanted
+    };
+    (if preferred { 0 } else { 1 }, distance)
+}
+
+// Rank a slant: italic and oblique stand in for each other before upright.
+fn slant_rank(wanted: Slant, slant: Slant) -> (u8, f32) {
+    use Slant::*;
+    let rank = match (wanted, slant) {
+        _ if wanted == slant => 0,
+        (Italic, Oblique) | (Oblique, Italic) | (Upright, Oblique) => 1,
+        _ => 2,
+    };
+    (rank, 0.0)
+}
+
+// Rank a weight: from 400 to 500, heavier weights up to 500 come first, then
+// lighter ones, then heavier ones beyond 500.  Below 400, lighter weights
+// come first, and above 500 heavier weights come first.
+fn weight_rank(wanted: u16, weight: u16) -> (u8, f32) {
+    let distance = f32::from(weight.max(wanted) - weight.min(wanted));
+    let rank = if (400..=500).contains(&wanted) {
+        if weight >= wanted && weight <= 500 {
+            0
+        } else if weight < wanted {
+            1
+        } else {
+            2
+        }
+    } else if wanted < 400 {
+        if weight <= wanted {
+            0
+        } else {
+            1
+        }
+    } else if weight >= wanted {
+        0
+    } else {
+        1
+    };
+    (rank, distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Pick the closest of `variants` to `wanted`.
+    fn closest(wanted: Variant, variants: &[Variant]) -> Option<Variant> {
+        Some(variants[wanted.closest(variants.iter().copied())?])
+    }
+
+    #[test]
+    fn weight() {
+        let cases: [(u16, &[u16], u16); 11] = [
+            (400, &[100, 300, 400, 500, 600, 900], 400),
+            // From 400 to 500: heavier up to 500, then lighter, then heavier.
+            (450, &[300, 400, 500, 600], 500),
+            (450, &[300, 400, 600], 400),
+            (450, &[600, 900], 600),
+            (500, &[400, 600], 400),
+            (400, &[300, 600], 300),
+            // Below 400: lighter, then heavier.
+            (300, &[100, 250, 350, 600], 250),
+            (300, &[350, 600], 350),
+            // Above 500: heavier, then lighter.
+            (700, &[400, 600, 800, 900], 800),
+            (700, &[400, 600], 600),
+            (600, &[500, 700], 700),
+        ];
+        for (wanted, weights, expected) in cases.iter() {
+            let wanted = Variant {
+                weight: *wanted,
+                ..Variant::REGULAR
+            };
+            let variants: Vec<Variant> = weights
+                .iter()
+                .map(|&weight| Variant {
+                    weight,
+                    ..Variant::REGULAR
+                })
+                .collect();
+            let weight = closest(wanted, &variants).map(|v| v.weight);
+            assert_eq!(
+                weight,
+                Some(*expected),
+                "{} of {:?}",
+                wanted.weight,
+                weights
+            );
+        }
+    }
+
+    #[test]
+    fn slant() {
+        use Slant::*;
+        let cases: [(Slant, &[Slant], Slant); 7] = [
+            (Italic, &[Upright, Oblique, Italic], Italic),
+            // Italic and oblique stand in for each other before upright.
+            (Italic, &[Upright, Oblique], Oblique),
+            (Oblique, &[Upright, Italic], Italic),
+            (Italic, &[Upright], Upright),
+            // Upright falls back to oblique before italic.
+            (Upright, &[Italic, Oblique], Oblique),
+            (Upright, &[Italic], Italic),
+            (Upright, &[Italic, Upright], Upright),
+        ];
+        for (wanted, slants, expected) in cases.iter() {
+            let wanted = Variant {
+                slant: *wanted,
+                ..Variant::REGULAR
+            };
+            let variants: Vec<Variant> = slants
+                .iter()
+                .map(|&slant| Variant {
+                    slant,
+                    ..Variant::REGULAR
+                })
+                .collect();
+            let slant = closest(wanted, &variants).map(|v| v.slant);
+            assert_eq!(
+                slant,
+                Some(*expected),
+                "{:?} of {:?}",
+                wanted.slant,
+                slants
+            );
+        }
+    }
+
+    #[test]
+    fn stretch() {
+        let cases: [(f32, &[f32], f32); 7] = [
+            (100.0, &[75.0, 100.0, 125.0], 100.0),
+            // Normal or narrower: narrower, then wider.
+            (100.0, &[75.0, 125.0], 75.0),
+            (87.5, &[50.0, 75.0, 100.0], 75.0),
+            (100.0, &[112.5, 200.0], 112.5),
+            // Wider: wider, then narrower.
+            (112.5, &[100.0, 125.0], 125.0),
+            (125.0, &[100.0, 150.0, 200.0], 150.0),
+            (150.0, &[100.0, 125.0], 125.0),
+        ];
+        for (wanted, stretches, expected) in cases.iter() {
+            let wanted = Variant {
+                stretch: *wanted,
+                ..Variant::REGULAR
+            };
+            let variants: Vec<Variant> = stretches
+                .iter()
+                .map(|&stretch| Variant {
+                    stretch,
+                    ..Variant::REGULAR
+                })
+                .collect();
+            let stretch = closest(wanted, &variants).map(|v| v.stretch);
+            assert_eq!(
+                stretch,
+                Some(*expected),
+                "{} of {:?}",
+                wanted.stretch,
+                stretches
+            );
+        }
+    }
+
+    #[test]
+    fn order() {
+        let bold_italic = Variant {
+            weight: 700,
+            slant: Slant::Italic,
+            stretch: 100.0,
+        };
+        // Stretch is matched before slant and weight, and slant before weight.
+        let wide = Variant {
+            stretch: 125.0,
+            ..bold_italic
+        };
+        let bold = Variant {
+            weight: 700,
+            ..Variant::REGULAR
+        };
+        let italic = Variant {
+            slant: Slant::Italic,
+            ..Variant::REGULAR
+        };
+        let cases: [(&[Variant], Option<Variant>); 4] = [
+            (&[wide, Variant::REGULAR], Some(Variant::REGULAR)),
+            (&[bold, italic], Some(italic)),
+            (&[Variant::REGULAR, bold_italic, bold], Some(bold_italic)),
+            (&[], None),
+        ];
+        for (variants, expected) in cases.iter() {
+            assert_eq!(closest(bold_italic, variants), *expected);
+        }
+    }
+}
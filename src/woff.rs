@@ -0,0 +1,782 @@
+// Copyright © 2018-2022 The Fonterator Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+//! Decoding WOFF and WOFF2 files into the TTF/OTF (sfnt) data they wrap.
+
+use brotli_decompressor::Decompressor;
+use std::convert::TryFrom;
+use std::io::Read;
+
+// A table of an sfnt file: its tag and data.
+type Table = ([u8; 4], Vec<u8>);
+
+/// Decode a WOFF 1.0 or WOFF2 file into the TTF or OTF data it wraps, which
+/// [`Font::push()`](crate::Font::push) and the other methods that take font
+/// data accept.  Returns `None` if `data` isn't a WOFF or WOFF2 file, or is
+/// corrupt.  Font collections in WOFF2 files aren't supported.  Requires
+/// feature = "woff", enabled by default.
+///
+/// The `Font` borrows the decoded data, like any other font data.
+///
+/// ```rust,no_run
+/// let woff2 = std::fs::read("OpenSans-Regular.woff2").unwrap();
+/// let ttf = fonterator::decode_woff(&woff2).unwrap();
+/// let font = fonterator::Font::new().push(&ttf[..]).unwrap();
+/// ```
+pub fn decode_woff(data: &[u8]) -> Option<Vec<u8>> {
+    match data.get(..4)? {
+        b"wOFF" => woff(data),
+        b"wOF2" => woff2(data),
+        _ => None,
+    }
+}
+
+/// Whether font data is a WOFF or WOFF2 file (by its signature only).
+pub(crate) fn is_woff(data: &[u8]) -> bool {
+    matches!(data.get(..4), Some(b"wOFF") | Some(b"wOF2"))
+}
+
+// Decode a WOFF 1.0 file, where each table is compressed with zlib on its own.
+fn woff(data: &[u8]) -> Option<Vec<u8>> {
+    let mut header = Stream::new(data.get(4..44)?);
+    let flavor = header.u32()?;
+    header.skip(4)?;
+    let count = usize::from(header.u16()?);
+
+    let mut directory = Stream::new(data.get(44..)?);
+    let mut tables = Vec::with_capacity(count);
+    for _ in 0..count {
+        let tag = directory.tag()?;
+        let offset = directory.u32()? as usize;
+        let len = directory.u32()? as usize;
+        let orig_len = directory.u32()? as usize;
+        directory.skip(4)?;
+
+        let table = data.get(offset..offset.checked_add(len)?)?;
+        // Tables that wouldn't get smaller are stored uncompressed.
+        let table = if len < orig_len {
+            miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(
+                table, orig_len,
+            )
+            .ok()?
+        } else {
+            table.to_vec()
+        };
+        if table.len() != orig_len {
+            return None;
+        }
+        tables.push((tag, table));
+    }
+    sfnt(flavor, tables)
+}
+
+// An entry in the table directory of a WOFF2 file.
+struct Entry {
+    tag: [u8; 4],
+    // Preprocessing transformation version.
+    version: u8,
+    // Length of the table once decoded.
+    orig_len: usize,
+    // Length of the (possibly transformed) table in the decompressed data.
+    len: usize,
+}
+
+impl Entry {
+    // Whether the table is transformed; the `glyf` and `loca` tables are
+    // unless they have version 3, and other tables are unless version 0.
+    fn transformed(&self) -> bool {
+        if [*b"glyf", *b"loca"].contains(&self.tag) {
+            self.version != 3
+        } else {
+            self.version != 0
+        }
+    }
+}
+
+// Decode a WOFF2 file, where all tables are compressed together with Brotli,
+// and `glyf`, `loca` and `hmtx` may be transformed to compress better.
+fn woff2(data: &[u8]) -> Option<Vec<u8>> {
+    let mut header = Stream::new(data.get(4..48)?);
+    let flavor = header.u32()?;
+    if &flavor.to_be_bytes() == b"ttcf" {
+        return None;
+    }
+    header.skip(4)?;
+    let count = usize::from(header.u16()?);
+    header.skip(6)?;
+    let compressed_len = header.u32()? as usize;
+
+    let mut directory = Stream::new(data.get(48..)?);
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        let flags = directory.u8()?;
+        let tag = match flags & 0x3F {
+            0x3F => directory.tag()?,
+            known => KNOWN_TAGS[usize::from(known)],
+        };
+        let mut entry = Entry {
+            tag,
+            version: flags >> 6,
+            orig_len: directory.base128()? as usize,
+            len: 0,
+        };
+        entry.len = if entry.transformed() {
+            directory.base128()? as usize
+        } else {
+            entry.orig_len
+        };
+        entries.push(entry);
+    }
+
+    let start = 48 + directory.at;
+    let compressed = data.get(start..start.checked_add(compressed_len)?)?;
+    let total = entries
+        .iter()
+        .try_fold(0usize, |total, entry| total.checked_add(entry.len))?;
+    let mut decompressed = Vec::new();
+    Decompressor::new(compressed, 4096)
+        .take(total as u64)
+        .read_to_end(&mut decompressed)
+        .ok()?;
+    if decompressed.len() != total {
+        return None;
+    }
+
+    let mut tables: Vec<(&Entry, &[u8])> = Vec::with_capacity(count);
+    let mut stream = Stream::new(&decompressed);
+    for entry in entries.iter() {
+        tables.push((entry, stream.bytes(entry.len)?));
+    }
+    let table = |tag: &[u8; 4]| {
+        tables.iter().find(|(entry, _)| &entry.tag == tag).copied()
+    };
+
+    // `glyf` and `loca` are rebuilt together, and `hmtx` from the result.
+    let glyf = match table(b"glyf") {
+        Some((entry, data)) if entry.transformed() => {
+            if entry.version != 0 {
+                return None;
+            }
+            Some(glyf(data)?)
+        }
+        _ => None,
+    };
+    let mut decoded = Vec::with_capacity(count);
+    for (entry, data) in tables.iter() {
+        let table = match (&entry.tag, entry.transformed()) {
+            (_, false) => data.to_vec(),
+            (b"glyf", true) => glyf.as_ref()?.glyf.clone(),
+            (b"loca", true) => {
+                let loca = glyf.as_ref()?.loca.clone();
+                if loca.len() != entry.orig_len {
+                    return None;
+                }
+                loca
+            }
+            (b"hmtx", true) if entry.version == 1 => {
+                let hhea = table(b"hhea")?.1;
+                let metrics = usize::from(Stream::new(hhea.get(34..)?).u16()?);
+                hmtx(data, metrics, &glyf.as_ref()?.x_mins)?
+            }
+            _ => return None,
+        };
+        decoded.push((entry.tag, table));
+    }
+    sfnt(flavor, decoded)
+}
+
+// The `glyf` and `loca` tables rebuilt from a transformed `glyf` table.
+struct Glyf {
+    glyf: Vec<u8>,
+    loca: Vec<u8>,
+    // Left edge of each glyph's bounding box, for rebuilding `hmtx`.
+    x_mins: Vec<i16>,
+}
+
+// Rebuild the `glyf` and `loca` tables from a transformed `glyf` table,
+// which splits the glyphs into streams of like values.
+fn glyf(table: &[u8]) -> Option<Glyf> {
+    let mut header = Stream::new(table);
+    header.skip(2)?;
+    let options = header.u16()?;
+    let num_glyphs = usize::from(header.u16()?);
+    let short_loca = header.u16()? == 0;
+
+    let mut at = 36usize;
+    let mut stream = || {
+        let len = header.u32()? as usize;
+        let stream = Stream::new(table.get(at..at.checked_add(len)?)?);
+        at += len;
+        Some(stream)
+    };
+    let mut contours = stream()?;
+    let mut points = stream()?;
+    let mut flags = stream()?;
+    let mut glyphs = stream()?;
+    let mut composites = stream()?;
+    let mut bboxes = stream()?;
+    let mut instructions = stream()?;
+    let bitmap_len = num_glyphs.div_ceil(8);
+    let overlaps = match options & 1 {
+        0 => None,
+        _ => Some(table.get(at..at + bitmap_len)?),
+    };
+    let bbox_bitmap = bboxes.bytes(4 * num_glyphs.div_ceil(32))?;
+    let bit = |bitmap: &[u8], glyph: usize| {
+        bitmap[glyph / 8] & (0x80 >> (glyph % 8)) != 0
+    };
+
+    let mut glyf = Vec::new();
+    let mut offsets = Vec::with_capacity(num_glyphs + 1);
+    let mut x_mins = Vec::with_capacity(num_glyphs);
+    for glyph in 0..num_glyphs {
+        offsets.push(glyf.len());
+        let num_contours = contours.i16()?;
+        let has_bbox = bit(bbox_bitmap, glyph);
+        if num_contours == 0 {
+            if has_bbox {
+                return None;
+            }
+            x_mins.push(0);
+            continue;
+        }
+
+        glyf.extend_from_slice(&num_contours.to_be_bytes());
+        let mut code = &[][..];
+        if num_contours < 0 {
+            // Composite glyphs always have an explicit bounding box.
+            if !has_bbox {
+                return None;
+            }
+            let bbox = bboxes.bytes(8)?;
+            x_mins.push(Stream::new(bbox).i16()?);
+            glyf.extend_from_slice(bbox);
+            let (components, has_code) = components(&mut composites)?;
+            glyf.extend_from_slice(components);
+            if has_code {
+                code = instructions.bytes(glyphs.u255()?)?;
+                glyf.extend_from_slice(&(code.len() as u16).to_be_bytes());
+            }
+            glyf.extend_from_slice(code);
+        } else {
+            let mut ends = Vec::with_capacity(num_contours as usize);
+            let mut num_points = 0;
+            for _ in 0..num_contours {
+                num_points += points.u255()?;
+                ends.push(u16::try_from(num_points.checked_sub(1)?).ok()?);
+            }
+            let mut outline = Vec::new();
+            let (mut x, mut y) = (0, 0);
+            for _ in 0..num_points {
+                let (on_curve, dx, dy) = triplet(flags.u8()?, &mut glyphs)?;
+                x = dx.wrapping_add(x);
+                y = dy.wrapping_add(y);
+                outline.push((on_curve, dx, dy, x, y));
+            }
+            code = instructions.bytes(glyphs.u255()?)?;
+
+            if has_bbox {
+                let bbox = bboxes.bytes(8)?;
+                x_mins.push(Stream::new(bbox).i16()?);
+                glyf.extend_from_slice(bbox);
+            } else {
+                let xs = outline.iter().map(|point| point.3);
+                let ys = outline.iter().map(|point| point.4);
+                let bbox = [
+                    xs.clone().min().unwrap_or(0),
+                    ys.clone().min().unwrap_or(0),
+                    xs.max().unwrap_or(0),
+                    ys.max().unwrap_or(0),
+                ];
+                x_mins.push(bbox[0] as i16);
+                for value in bbox.iter() {
+                    glyf.extend_from_slice(&(*value as i16).to_be_bytes());
+                }
+            }
+            for end in ends {
+                glyf.extend_from_slice(&end.to_be_bytes());
+            }
+            glyf.extend_from_slice(&(code.len() as u16).to_be_bytes());
+            glyf.extend_from_slice(code);
+            let overlap = overlaps.is_some_and(|bitmap| bit(bitmap, glyph));
+            simple(&mut glyf, &outline, overlap);
+        }
+        while glyf.len() % 4 != 0 {
+            glyf.push(0);
+        }
+    }
+    offsets.push(glyf.len());
+
+    let mut loca = Vec::new();
+    for offset in offsets {
+        if short_loca {
+            let offset = u16::try_from(offset / 2).ok()?;
+            loca.extend_from_slice(&offset.to_be_bytes());
+        } else {
+            let offset = u32::try_from(offset).ok()?;
+            loca.extend_from_slice(&offset.to_be_bytes());
+        }
+    }
+    Some(Glyf { glyf, loca, x_mins })
+}
+
+// Copy the components of a composite glyph from the composite stream,
+// returning them and whether the glyph has instructions.
+fn components<'d>(stream: &mut Stream<'d>) -> Option<(&'d [u8], bool)> {
+    const ARGS_ARE_WORDS: u16 = 0x0001;
+    const HAVE_SCALE: u16 = 0x0008;
+    const MORE_COMPONENTS: u16 = 0x0020;
+    const HAVE_X_AND_Y_SCALE: u16 = 0x0040;
+    const HAVE_TWO_BY_TWO: u16 = 0x0080;
+    const HAVE_INSTRUCTIONS: u16 = 0x0100;
+
+    let start = stream.at;
+    let mut has_code = false;
+    loop {
+        let flags = stream.u16()?;
+        has_code |= flags & HAVE_INSTRUCTIONS != 0;
+        // Glyph ID and offset or point numbers.
+        let mut len = if flags & ARGS_ARE_WORDS != 0 { 6 } else { 4 };
+        if flags & HAVE_SCALE != 0 {
+            len += 2;
+        } else if flags & HAVE_X_AND_Y_SCALE != 0 {
+            len += 4;
+        } else if flags & HAVE_TWO_BY_TWO != 0 {
+            len += 8;
+        }
+        stream.skip(len)?;
+        if flags & MORE_COMPONENTS == 0 {
+            return Some((&stream.data[start..stream.at], has_code));
+        }
+    }
+}
+
+// Decode a point of a simple glyph from its flag (with the on-curve bit, and
+// how its deltas are packed) and its bytes in the glyph stream.  Returns
+// whether it's on the curve, and its deltas from the previous point.
+fn triplet(flag: u8, glyphs: &mut Stream<'_>) -> Option<(bool, i32, i32)> {
+    let on_curve = flag & 0x80 == 0;
+    let flag = i32::from(flag & 0x7F);
+    let sign = |bit: i32, value: i32| {
+        if flag >> bit & 1 != 0 {
+            value
+        } else {
+            -value
+        }
+    };
+    let (dx, dy) = if flag < 10 {
+        let b0 = i32::from(glyphs.u8()?);
+        (0, sign(0, ((flag & 14) << 7) + b0))
+    } else if flag < 20 {
+        let b0 = i32::from(glyphs.u8()?);
+        (sign(0, (((flag - 10) & 14) << 7) + b0), 0)
+    } else if flag < 84 {
+        let (index, b0) = (flag - 20, i32::from(glyphs.u8()?));
+        (
+            sign(0, 1 + (index & 0x30) + (b0 >> 4)),
+            sign(1, 1 + ((index & 0x0C) << 2) + (b0 & 0x0F)),
+        )
+    } else if flag < 120 {
+        let (index, b) = (flag - 84, glyphs.bytes(2)?);
+        (
+            sign(0, 1 + ((index / 12) << 8) + i32::from(b[0])),
+            sign(1, 1 + (((index % 12) >> 2) << 8) + i32::from(b[1])),
+        )
+    } else if flag < 124 {
+        let b = glyphs.bytes(3)?;
+        let (b0, b1, b2) = (i32::from(b[0]), i32::from(b[1]), i32::from(b[2]));
+        (
+            sign(0, (b0 << 4) + (b1 >> 4)),
+            sign(1, ((b1 & 0x0F) << 8) + b2),
+        )
+    } else {
+        let b = glyphs.bytes(4)?;
+        let word = |i: usize| i32::from(b[i]) << 8 | i32::from(b[i + 1]);
+        (sign(0, word(0)), sign(1, word(2)))
+    };
+    Some((on_curve, dx, dy))
+}
+
+// Write the flags and coordinates of a simple glyph's points (on-curve bit,
+// deltas and absolute position), using one byte for deltas that fit.
+fn simple(
+    glyf: &mut Vec<u8>,
+    outline: &[(bool, i32, i32, i32, i32)],
+    overlap: bool,
+) {
+    const ON_CURVE: u8 = 0x01;
+    const X_SHORT: u8 = 0x02;
+    const Y_SHORT: u8 = 0x04;
+    const X_SAME_OR_POSITIVE: u8 = 0x10;
+    const Y_SAME_OR_POSITIVE: u8 = 0x20;
+    const OVERLAP_SIMPLE: u8 = 0x40;
+
+    // Flag bits and bytes for a delta.
+    let packed = |delta: i32, short: u8, same_or_positive: u8| match delta {
+        0 => (same_or_positive, None),
+        1..=255 => (short | same_or_positive, Some(vec![delta as u8])),
+        -255..=-1 => (short, Some(vec![-delta as u8])),
+        _ => (0, Some((delta as i16).to_be_bytes().to_vec())),
+    };
+    let mut xs = Vec::new();
+    let mut ys = Vec::new();
+    for (i, (on_curve, dx, dy, _, _)) in outline.iter().enumerate() {
+        let (x_flag, x) = packed(*dx, X_SHORT, X_SAME_OR_POSITIVE);
+        let (y_flag, y) = packed(*dy, Y_SHORT, Y_SAME_OR_POSITIVE);
+        let mut flag = x_flag | y_flag;
+        if *on_curve {
+            flag |= ON_CURVE;
+        }
+        if overlap && i == 0 {
+            flag |= OVERLAP_SIMPLE;
+        }
+        glyf.push(flag);
+        xs.extend(x.unwrap_or_default());
+        ys.extend(y.unwrap_or_default());
+    }
+    glyf.extend(xs);
+    glyf.extend(ys);
+}
+
+// Rebuild the `hmtx` table from a transformed one, which leaves out left
+// side bearings that equal the `xMin` of the glyph's bounding box.
+fn hmtx(table: &[u8], metrics: usize, x_mins: &[i16]) -> Option<Vec<u8>> {
+    let mut stream = Stream::new(table);
+    let flags = stream.u8()?;
+    let advances = stream.bytes(2 * metrics)?;
+    let mut hmtx = Vec::with_capacity(2 * metrics + 2 * x_mins.len());
+    for (glyph, x_min) in x_mins.iter().enumerate() {
+        let proportional = glyph < metrics;
+        if proportional {
+            hmtx.extend_from_slice(&advances[2 * glyph..][..2]);
+        }
+        let absent = if proportional { flags & 1 } else { flags & 2 };
+        let lsb = if absent != 0 { *x_min } else { stream.i16()? };
+        hmtx.extend_from_slice(&lsb.to_be_bytes());
+    }
+    Some(hmtx)
+}
+
+// Build an sfnt file from its tables, sorted by tag with checksums.
+fn sfnt(flavor: u32, mut tables: Vec<Table>) -> Option<Vec<u8>> {
+    if tables.is_empty() {
+        return None;
+    }
+    tables.sort_by_key(|table| table.0);
+    let padded = |len: usize| (len + 3) & !3;
+    let count = tables.len();
+    let size = tables
+        .iter()
+        .map(|table| padded(table.1.len()))
+        .sum::<usize>();
+    let mut sfnt = Vec::with_capacity(12 + 16 * count + size);
+
+    // Offset table, with the search parameters for a binary search.
+    let entry_selector = (usize::BITS - 1 - count.leading_zeros()) as usize;
+    let search_range = 16 << entry_selector;
+    sfnt.extend_from_slice(&flavor.to_be_bytes());
+    for value in [
+        count,
+        search_range,
+        entry_selector,
+        16 * count - search_range,
+    ] {
+        sfnt.extend_from_slice(&u16::try_from(value).ok()?.to_be_bytes());
+    }
+
+    let mut offset = 12 + 16 * count;
+    let mut head = None;
+    for (tag, table) in tables.iter_mut() {
+        if tag == b"head" && table.len() >= 12 {
+            // Zero `checkSumAdjustment` until the whole file's sum is known.
+            table[8..12].copy_from_slice(&[0; 4]);
+            head = Some(offset + 8);
+        }
+        sfnt.extend_from_slice(tag);
+        sfnt.extend_from_slice(&checksum(table).to_be_bytes());
+        sfnt.extend_from_slice(&u32::try_from(offset).ok()?.to_be_bytes());
+        sfnt.extend_from_slice(&u32::try_from(table.len()).ok()?.to_be_bytes());
+        offset += padded(table.len());
+    }
+    for (_, table) in tables.iter() {
+        sfnt.extend_from_slice(table);
+        sfnt.resize(padded(sfnt.len()), 0);
+    }
+    if let Some(head) = head {
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&sfnt));
+        sfnt[head..head + 4].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    Some(sfnt)
+}
+
+// Sum data as big-endian `u32`s, padded with zeros.
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+// A cursor over big-endian values in a byte slice.
+struct Stream<'d> {
+    data: &'d [u8],
+    at: usize,
+}
+
+impl<'d> Stream<'d> {
+    fn new(data: &'d [u8]) -> Self {
+        Self { data, at: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Option<&'d [u8]> {
+        let bytes = self.data.get(self.at..self.at.checked_add(len)?)?;
+        self.at += len;
+        Some(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Option<()> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn tag(&mut self) -> Option<[u8; 4]> {
+        let bytes = self.bytes(4)?;
+        Some([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        let bytes = self.bytes(2)?;
+        Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn i16(&mut self) -> Option<i16> {
+        Some(self.u16()? as i16)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.tag()?))
+    }
+
+    // Read a `UIntBase128`: 7 bits per byte, most significant first, with
+    // the high bit set on all but the last of up to 5 bytes.
+    fn base128(&mut self) -> Option<u32> {
+        let mut value = 0u32;
+        for i in 0..5 {
+            let byte = self.u8()?;
+            // Leading zeros and overflow aren't allowed.
+            if (i == 0 && byte == 0x80) || value >> 25 != 0 {
+                return None;
+            }
+            value = value << 7 | u32::from(byte & 0x7F);
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    // Read a `255UInt16`: one byte for small values, or a code byte then
+    // one or two more.
+    fn u255(&mut self) -> Option<usize> {
+        Some(match self.u8()? {
+            253 => usize::from(self.u16()?),
+            254 => 253 * 2 + usize::from(self.u8()?),
+            255 => 253 + usize::from(self.u8()?),
+            code => usize::from(code),
+        })
+    }
+}
+
+// Tags of the tables a WOFF2 table directory can refer to by index.
+const KNOWN_TAGS: [[u8; 4]; 63] = [
+    *b"cmap", *b"head", *b"hhea", *b"hmtx", *b"maxp", *b"name", *b"OS/2",
+    *b"post", *b"cvt ", *b"fpgm", *b"glyf", *b"loca", *b"prep", *b"CFF ",
+    *b"VORG", *b"EBDT", *b"EBLC", *b"gasp", *b"hdmx", *b"kern", *b"LTSH",
+    *b"PCLT", *b"VDMX", *b"vhea", *b"vmtx", *b"BASE", *b"GDEF", *b"GPOS",
+    *b"GSUB", *b"EBSC", *b"JSTF", *b"MATH", *b"CBDT", *b"CBLC", *b"COLR",
+    *b"CPAL", *b"SVG ", *b"sbix", *b"acnt", *b"avar", *b"bdat", *b"bloc",
+    *b"bsln", *b"cvar", *b"fdsc", *b"feat", *b"fmtx", *b"fvar", *b"gvar",
+    *b"hsty", *b"just", *b"lcar", *b"mort", *b"morx", *b"opbd", *b"prop",
+    *b"trak", *b"Zapf", *b"Silf", *b"Glat", *b"Gloc", *b"Feat", *b"Sill",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A font with five glyphs: a box with a hole and instructions, an empty
+    // space, an outline with point deltas of every size, one with a bounding
+    // box larger than its points, and a composite with instructions.  The
+    // WOFF2 file transforms `glyf`, `loca` and `hmtx`, and stores its tables
+    // in uncompressed Brotli meta-blocks.
+    const TTF: &[u8] = include_bytes!("woff/tiny.ttf");
+    const WOFF: &[u8] = include_bytes!("woff/tiny.woff");
+    const WOFF2: &[u8] = include_bytes!("woff/tiny.woff2");
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(decode_woff(WOFF).as_deref(), Some(TTF));
+        assert_eq!(decode_woff(WOFF2).as_deref(), Some(TTF));
+        assert_eq!(decode_woff(TTF), None);
+    }
+
+    #[test]
+    fn truncated() {
+        for woff in [WOFF, WOFF2] {
+            for len in 0..woff.len() {
+                assert_eq!(decode_woff(&woff[..len]), None, "{} bytes", len);
+            }
+        }
+    }
+
+    #[test]
+    fn corrupt() {
+        // Any byte may be wrong without panicking.
+        for woff in [WOFF, WOFF2] {
+            for at in 0..woff.len() {
+                for mask in [0x01, 0x80, 0xFF] {
+                    let mut corrupt = woff.to_vec();
+                    corrupt[at] ^= mask;
+                    let _ = decode_woff(&corrupt);
+                }
+            }
+        }
+
+        // The `cmap` table's `origLength` in WOFF.
+        let mut woff = WOFF.to_vec();
+        woff[44 + 15] += 1;
+        assert_eq!(decode_woff(&woff), None);
+        // Collections in WOFF2.
+        let mut woff2 = WOFF2.to_vec();
+        woff2[4..8].copy_from_slice(b"ttcf");
+        assert_eq!(decode_woff(&woff2), None);
+    }
+
+    #[test]
+    fn glyf_transform() {
+        #[rustfmt::skip]
+        let table = [
+            0, 0, 0, 0, 0, 2, 0, 0, // Version, options, glyphs, short loca
+            0, 0, 0, 4, 0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0, 5, // Stream lengths
+            0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0,
+            0, 0, 0, 1, // Contours: an empty glyph, and one with a contour
+            3, // Points
+            11, 1, 128 | 85, // Flags: (10, 0), (10, 200), off-curve (60, 100)
+            10, 200, 49, 99, 0, // Deltas, and no instructions
+            0, 0, 0, 0, // Bounding boxes: none given
+        ];
+        let rebuilt = glyf(&table).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(rebuilt.glyf, [
+            0, 1, 0, 10, 0, 0, 0, 60, 0, 200, // Contours, bounding box
+            0, 2, 0, 0, // Contour end, no instructions
+            0x33, 0x35, 0x16, // Flags
+            10, 50, 200, 100, // X and Y deltas
+            0, 0, 0, // Padding
+        ]);
+        assert_eq!(rebuilt.loca, [0, 0, 0, 0, 0, 12]);
+        assert_eq!(rebuilt.x_mins, [0, 10]);
+
+        // Composite glyphs need a bounding box.
+        let mut composite = table;
+        composite[38..40].copy_from_slice(&(-1i16).to_be_bytes());
+        assert!(glyf(&composite).is_none());
+        // Streams can't be longer than the table.
+        let mut long = table;
+        long[27] = 6;
+        assert!(glyf(&long).is_none());
+    }
+
+    #[test]
+    fn hmtx_transform() {
+        // Two glyphs with advances, and one more with only a side bearing.
+        let x_mins = [10, 20, 30];
+        let cases: [(&[u8], &[u8]); 4] = [
+            (
+                &[0, 0, 100, 0, 200, 0, 1, 0, 2, 0, 3],
+                &[0, 100, 0, 1, 0, 200, 0, 2, 0, 3],
+            ),
+            (
+                &[1, 0, 100, 0, 200, 0, 3],
+                &[0, 100, 0, 10, 0, 200, 0, 20, 0, 3],
+            ),
+            (
+                &[2, 0, 100, 0, 200, 0, 1, 0, 2],
+                &[0, 100, 0, 1, 0, 200, 0, 2, 0, 30],
+            ),
+            (&[3, 0, 100, 0, 200], &[0, 100, 0, 10, 0, 200, 0, 20, 0, 30]),
+        ];
+        for (table, expected) in cases.iter() {
+            let decoded = hmtx(table, 2, &x_mins);
+            assert_eq!(
+                decoded.as_deref(),
+                Some(*expected),
+                "flags {}",
+                table[0]
+            );
+        }
+        assert_eq!(hmtx(&[0, 0, 100, 0, 200, 0, 1], 2, &x_mins), None);
+    }
+
+    #[test]
+    fn triplets() {
+        let cases: [(&[u8], bool, i32, i32); 9] = [
+            (&[1, 200], true, 0, 200),
+            (&[8, 76], true, 0, -1100),
+            (&[11, 10], true, 10, 0),
+            (&[146, 255], false, -1279, 0),
+            (&[39, 35], true, 19, 4),
+            (&[68, 240], true, -64, -1),
+            (&[233, 43, 187], false, 300, -700),
+            (&[121, 255, 240, 1], true, 4095, -1),
+            (&[126, 19, 136, 127, 255], true, -5000, 32767),
+        ];
+        for &(bytes, on_curve, dx, dy) in cases.iter() {
+            let mut glyphs = Stream::new(&bytes[1..]);
+            let point = triplet(bytes[0], &mut glyphs);
+            assert_eq!(point, Some((on_curve, dx, dy)));
+            assert_eq!(glyphs.at, bytes.len() - 1);
+        }
+        assert_eq!(triplet(124, &mut Stream::new(&[0, 1, 0])), None);
+    }
+
+    #[test]
+    fn base128() {
+        let cases: [(&[u8], Option<u32>); 7] = [
+            (&[0x3F], Some(63)),
+            (&[0x9F, 0x47], Some(0xFC7)),
+            (&[0xFF, 0xFF, 0xFF, 0x7F], Some(0xFFF_FFFF)),
+            (&[0x8F, 0xFF, 0xFF, 0xFF, 0x7F], Some(0xFFFF_FFFF)),
+            (&[0x80, 0x01], None),
+            (&[0x90, 0x80, 0x80, 0x80, 0x00], None),
+            (&[0x81, 0x80, 0x80, 0x80, 0x80, 0x00], None),
+        ];
+        for (bytes, value) in cases.iter() {
+            assert_eq!(Stream::new(bytes).base128(), *value, "{:?}", bytes);
+        }
+    }
+
+    #[test]
+    fn u255() {
+        let cases: [(&[u8], Option<usize>); 6] = [
+            (&[252], Some(252)),
+            (&[255, 5], Some(258)),
+            (&[254, 5], Some(511)),
+            (&[253, 1, 0], Some(256)),
+            (&[253, 1], None),
+            (&[], None),
+        ];
+        for (bytes, value) in cases.iter() {
+            assert_eq!(Stream::new(bytes).u255(), *value, "{:?}", bytes);
+        }
+    }
+}